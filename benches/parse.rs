@@ -3,7 +3,7 @@
 extern crate ion;
 extern crate test;
 
-use ion::{Ion, Parser};
+use ion::{Ion, IonIndex, Parser};
 use test::{Bencher, black_box};
 
 const DEF_HOTEL_ON_START: &str = include_str!("data/def_hotel_on_start.ion");
@@ -104,8 +104,84 @@ mod parse_filtered {
     }
 }
 
+mod scanner {
+    use super::*;
+
+    // a section with a table of `rows` rows of mostly ASCII and some multi-byte cells
+    fn large_table(rows: usize) -> String {
+        let mut ion = String::from("[TABLE]\n| code | name | city | price |\n|------|------|------|-------|\n");
+        for i in 0..rows {
+            ion.push_str(&format!("| H{} | Hotel {} | Zürich | {}.50 |\n", i, i, i % 300));
+        }
+        ion
+    }
+
+    // `sections` sections of entries and rows followed by the section `LAST`
+    fn many_sections(sections: usize) -> String {
+        let mut ion = String::new();
+        for i in 0..sections {
+            ion.push_str(&format!("[SECTION.{}]\nname = \"Section {}\"\nnote = \"skipped\"\n| a | b | c |\n| 1 | 2 | 3 |\n", i, i));
+        }
+        ion.push_str("[LAST]\nname = \"last\"\n");
+        ion
+    }
+
+    #[bench]
+    fn large_table_of_ion(bencher: &mut Bencher) {
+        let ion = large_table(10_000);
+        bencher.iter(|| {
+            black_box(Parser::new(&ion).read().unwrap())
+        })
+    }
+
+    #[bench]
+    fn skipped_sections_of_ion(bencher: &mut Bencher) {
+        let ion = many_sections(10_000);
+        bencher.iter(|| {
+            black_box(Parser::new_filtered(&ion, vec!["LAST"]).read().unwrap())
+        })
+    }
+}
+
+#[cfg(feature = "parallel")]
+mod parse_parallel {
+    use super::*;
+
+    #[bench]
+    fn section_on_end_of_ion(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let result = Ion::from_str_parallel(DEF_HOTEL_ON_END, 4);
+            black_box(result.unwrap())
+        })
+    }
+}
+
+mod index {
+    use super::*;
+
+    #[bench]
+    fn section_on_end_of_ion(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let mut index = IonIndex::new(DEF_HOTEL_ON_END);
+            black_box(index.fetch("DEF.HOTEL").unwrap().rows.len())
+        })
+    }
+
+    #[bench]
+    fn section_on_end_of_ion_indexed(bencher: &mut Bencher) {
+        let index = IonIndex::new(DEF_HOTEL_ON_END);
+        bencher.iter(|| {
+            black_box(index.parse("DEF.HOTEL").unwrap())
+        })
+    }
+}
+
 
 //test parse::section_on_start_of_ion          ... bench:   4,187,092 ns/iter (+/- 125,166)
 //test parse::section_on_end_of_ion            ... bench:   4,223,583 ns/iter (+/- 155,651)
 //test parse_filtered::section_on_start_of_ion ... bench:      15,027 ns/iter (+/- 1,612)
 //test parse_filtered::section_on_end_of_ion   ... bench:     962,318 ns/iter (+/- 31,853)
+//test scanner::large_table_of_ion             ... bench:   8,426,075 ns/iter (+/- 4,042,583), 10,923,447 with Peekable<CharIndices>
+//test scanner::skipped_sections_of_ion        ... bench:     704,254 ns/iter (+/- 245,289),  1,496,437 with Peekable<CharIndices>
+//  following strings and arrays with the `Splitter` costs 5,880,101 against 2,333,001 for the search for `[`, in a run
+//  where large_table_of_ion took 8,393,199
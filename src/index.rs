@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use parser::offset_errors;
+use split::section_starts;
+use {IonError, Parser, Section};
+
+/// Random access to the sections of ion text: `new` finds the section headers in one pass
+/// without parsing, `get` parses a single section when it is first asked for and keeps it.
+/// Looking up a section costs as much as parsing it, wherever it is in the input.
+///
+/// Like `Parser::read`, the last definition of a section defined more than once is used and
+/// the "root" section only exists when there are no sections.
+///
+/// ```
+/// use ion::IonIndex;
+///
+/// let input = "[CONTRACT]\nid = 1\n[DEF.HOTEL]\n| code | name |\n| H1 | Hilton |\n";
+/// let mut index = IonIndex::new(input);
+/// assert_eq!(vec!["CONTRACT", "DEF.HOTEL"], index.names().collect::<Vec<_>>());
+///
+/// let hotels = index.fetch("DEF.HOTEL").unwrap();
+/// assert_eq!(2, hotels.rows.len());
+/// ```
+#[derive(Debug)]
+pub struct IonIndex<'a> {
+    input: &'a str,
+    file: Option<String>,
+    // the byte range of each section, from its header to the next header
+    ranges: BTreeMap<&'a str, Range<usize>>,
+    cache: BTreeMap<String, Section>,
+}
+
+impl<'a> IonIndex<'a> {
+    pub fn new(input: &'a str) -> IonIndex<'a> {
+        let starts = section_starts(input);
+        let mut ranges = BTreeMap::new();
+        if starts.is_empty() {
+            ranges.insert("root", 0..input.len());
+        }
+        for (i, &(start, name)) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).map_or(input.len(), |next| next.0);
+            ranges.insert(name, start..end);
+        }
+
+        IonIndex { input, file: None, ranges, cache: BTreeMap::new() }
+    }
+
+    /// Names the file the input was read from, see `Parser::with_file_name`
+    pub fn with_file_name<S: Into<String>>(mut self, file: S) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// The names of all sections in name order
+    pub fn names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.ranges.keys().cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.ranges.contains_key(name)
+    }
+
+    /// The byte range of the section `name` in the input, from its header to the next header
+    pub fn range(&self, name: &str) -> Option<Range<usize>> {
+        self.ranges.get(name).cloned()
+    }
+
+    /// Parses the section `name` every time it is called, `None` if there is no such section
+    pub fn parse(&self, name: &str) -> Result<Option<Section>, IonError> {
+        let range = match self.range(name) {
+            Some(range) => range,
+            None => return Ok(None),
+        };
+
+        let mut parser = self.parser(&self.input[range.clone()]);
+        match parser.read() {
+            // a header the `Splitter` does not see, like one after an entry on the same line,
+            // starts another section in the range
+            Some(ref map) if map.len() > 1 => self.parse_all(name),
+            Some(mut map) => Ok(Some(map.remove(name).unwrap_or_else(Section::new))),
+            None => {
+                let mut errors = parser.errors;
+                offset_errors(&mut errors, range.start, self.input[..range.start].matches('\n').count());
+                Err(IonError::ParserErrors(errors))
+            },
+        }
+    }
+
+    // parses the whole input for the section `name`
+    fn parse_all(&self, name: &str) -> Result<Option<Section>, IonError> {
+        let mut parser = self.parser(self.input);
+        match parser.read() {
+            Some(mut map) => Ok(map.remove(name)),
+            None => Err(IonError::ParserErrors(parser.errors)),
+        }
+    }
+
+    fn parser<'b>(&self, input: &'b str) -> Parser<'b> {
+        let parser = Parser::new(input);
+        match self.file {
+            Some(ref file) => parser.with_file_name(file.clone()),
+            None => parser,
+        }
+    }
+
+    /// Parses the section `name` the first time it is asked for, later calls return the
+    /// same section
+    pub fn get(&mut self, name: &str) -> Result<Option<&Section>, IonError> {
+        if !self.cache.contains_key(name) {
+            if let Some(section) = self.parse(name)? {
+                self.cache.insert(name.to_owned(), section);
+            }
+        }
+        Ok(self.cache.get(name))
+    }
+
+    /// like get, only a missing section is an `IonError::MissingSection`
+    pub fn fetch(&mut self, name: &str) -> Result<&Section, IonError> {
+        self.get(name)?.ok_or(IonError::MissingSection(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IonIndex;
+    use {Ion, IonError, Parser, Value};
+
+    #[test]
+    fn looks_up_sections() {
+        let input = "x = 0\n[A]\ntext = \"\"\"\n[B]\n\"\"\"\n[C]\n| 1 |\n[A]\ny = 2\n";
+        let mut index = IonIndex::new(input);
+        assert_eq!(vec!["A", "C"], index.names().collect::<Vec<_>>());
+        assert!(!index.contains("B") && !index.contains("root"));
+        assert_eq!(Some(input.find("[C]").unwrap()..input.rfind("[A]").unwrap()), index.range("C"));
+
+        let ion: Ion = input.parse().unwrap();
+        assert_eq!(ion.get("C"), index.get("C").unwrap());
+        assert_eq!(ion.get("A"), index.get("A").unwrap());
+        assert_eq!(Some(&Value::Integer(2)), index.fetch("A").unwrap().get("y"));
+        assert!(index.get("D").unwrap().is_none());
+        assert!(matches!(index.fetch("D"), Err(IonError::MissingSection(_))));
+
+        let mut index = IonIndex::new("x = 1\n");
+        assert_eq!(Some(&Value::Integer(1)), index.fetch("root").unwrap().get("x"));
+    }
+
+    #[test]
+    fn header_after_an_entry() {
+        let input = "[Z]\nx = 1 [B]\ny = 2\n";
+        let ion: Ion = input.parse().unwrap();
+
+        let mut index = IonIndex::new(input);
+        assert_eq!(Some(&Value::Integer(1)), index.fetch("Z").unwrap().get("x"));
+        assert_eq!(ion.get("Z"), index.get("Z").unwrap());
+    }
+
+    #[test]
+    fn errors_have_positions_in_the_input() {
+        let input = "[A]\nx = 1\n[B]\n\ny = [1, ?]\n";
+        let mut p = Parser::new(input).with_file_name("a.ion");
+        p.read();
+
+        let index = IonIndex::new(input).with_file_name("a.ion");
+        assert!(index.parse("A").is_ok());
+        match index.parse("B") {
+            Err(IonError::ParserErrors(errors)) => {
+                assert_eq!(p.errors[0].to_string(), errors[0].to_string());
+                assert_eq!((p.errors[0].lo, p.errors[0].hi), (errors[0].lo, errors[0].hi));
+            },
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use { Date, DateTime, Ion, IonError, Parser, Section, Time, Value };
+
+/// A `Value` that borrows its strings from the parsed input, see `IonRef`
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValueRef<'a> {
+    Null,
+    String(Cow<'a, str>),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Date(Date),
+    Time(Time),
+    DateTime(DateTime),
+    Reference(Cow<'a, str>),
+    Array(Vec<ValueRef<'a>>),
+    Dictionary(BTreeMap<Cow<'a, str>, ValueRef<'a>>),
+}
+
+impl<'a> ValueRef<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            ValueRef::String(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match *self {
+            ValueRef::Integer(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            ValueRef::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_boolean(&self) -> Option<bool> {
+        match *self {
+            ValueRef::Boolean(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<ValueRef<'a>>> {
+        match *self {
+            ValueRef::Array(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ValueRef<'a>> {
+        match *self {
+            ValueRef::Dictionary(ref v) => v.get(name),
+            _ => None,
+        }
+    }
+
+    /// Copies the borrowed strings into a `Value`
+    pub fn into_owned(self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::String(v) => Value::String(v.into_owned()),
+            ValueRef::Integer(v) => Value::Integer(v),
+            ValueRef::Float(v) => Value::Float(v),
+            ValueRef::Boolean(v) => Value::Boolean(v),
+            ValueRef::Date(v) => Value::Date(v),
+            ValueRef::Time(v) => Value::Time(v),
+            ValueRef::DateTime(v) => Value::DateTime(v),
+            ValueRef::Reference(v) => Value::Reference(v.into_owned()),
+            ValueRef::Array(v) => Value::Array(v.into_iter().map(ValueRef::into_owned).collect()),
+            ValueRef::Dictionary(v) => {
+                Value::Dictionary(v.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect())
+            },
+        }
+    }
+
+    /// Copies the borrowed strings, so that the value no longer borrows the input
+    pub fn into_static(self) -> ValueRef<'static> {
+        ValueRef::from(self.into_owned())
+    }
+}
+
+impl<'a> From<Value> for ValueRef<'a> {
+    fn from(value: Value) -> ValueRef<'a> {
+        match value {
+            Value::Null => ValueRef::Null,
+            Value::String(v) => ValueRef::String(Cow::Owned(v)),
+            Value::Integer(v) => ValueRef::Integer(v),
+            Value::Float(v) => ValueRef::Float(v),
+            Value::Boolean(v) => ValueRef::Boolean(v),
+            Value::Date(v) => ValueRef::Date(v),
+            Value::Time(v) => ValueRef::Time(v),
+            Value::DateTime(v) => ValueRef::DateTime(v),
+            Value::Reference(v) => ValueRef::Reference(Cow::Owned(v)),
+            Value::Array(v) => ValueRef::Array(v.into_iter().map(ValueRef::from).collect()),
+            Value::Dictionary(v) => {
+                ValueRef::Dictionary(v.into_iter().map(|(k, v)| (Cow::Owned(k), ValueRef::from(v))).collect())
+            },
+        }
+    }
+}
+
+impl<'a> From<ValueRef<'a>> for Value {
+    fn from(value: ValueRef<'a>) -> Value {
+        value.into_owned()
+    }
+}
+
+/// A `Section` that borrows its keys and strings from the parsed input, see `IonRef`
+#[derive(Debug, PartialEq, Clone)]
+pub struct SectionRef<'a> {
+    pub dictionary: BTreeMap<Cow<'a, str>, ValueRef<'a>>,
+    pub rows: Vec<Vec<ValueRef<'a>>>,
+}
+
+impl<'a> SectionRef<'a> {
+    pub fn with_capacity(n: usize) -> SectionRef<'a> {
+        SectionRef {
+            dictionary: BTreeMap::new(),
+            rows: Vec::with_capacity(n),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ValueRef<'a>> {
+        self.dictionary.get(name)
+    }
+
+    pub fn into_owned(self) -> Section {
+        Section {
+            dictionary: self.dictionary.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect(),
+            rows: self.rows.into_iter().map(|row| row.into_iter().map(ValueRef::into_owned).collect()).collect(),
+        }
+    }
+}
+
+/// An `Ion` that borrows from the input it was parsed from: keys, section names, table cells
+/// and strings are slices of the input unless they contain escape sequences, which saves an
+/// allocation for each of them. Multi-line strings are also owned when their indentation or
+/// carriage returns are removed.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use ion::{IonRef, ValueRef};
+///
+/// let input = "[HOTEL]\nname = \"Hilton\"";
+/// let ion = IonRef::parse(input).unwrap();
+/// let name = ion.get("HOTEL").and_then(|s| s.get("name"));
+/// assert!(matches!(name, Some(ValueRef::String(Cow::Borrowed("Hilton")))));
+/// ```
+#[derive(Debug)]
+pub struct IonRef<'a> {
+    sections: BTreeMap<Cow<'a, str>, SectionRef<'a>>,
+}
+
+impl<'a> IonRef<'a> {
+    pub fn new(map: BTreeMap<Cow<'a, str>, SectionRef<'a>>) -> IonRef<'a> {
+        IonRef { sections: map }
+    }
+
+    pub fn parse(s: &'a str) -> Result<IonRef<'a>, IonError> {
+        let mut parser = Parser::new(s);
+        match parser.read_borrowed() {
+            Some(sections) => Ok(IonRef::new(sections)),
+            None => Err(IonError::ParserErrors(parser.errors)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SectionRef<'a>> {
+        self.sections.get(key)
+    }
+
+    pub fn fetch(&self, key: &str) -> Result<&SectionRef<'a>, IonError> {
+        self.get(key).ok_or(IonError::MissingSection(key.to_owned()))
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<SectionRef<'a>> {
+        self.sections.remove(key)
+    }
+
+    pub fn iter(&self) -> ::std::collections::btree_map::Iter<'_, Cow<'a, str>, SectionRef<'a>> {
+        self.sections.iter()
+    }
+
+    /// Copies all borrowed strings into an `Ion`
+    pub fn into_owned(self) -> Ion {
+        Ion::new(self.sections.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect())
+    }
+}
+
+impl<'a> From<IonRef<'a>> for Ion {
+    fn from(ion: IonRef<'a>) -> Ion {
+        ion.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use { Ion, IonRef, Value, ValueRef };
+
+    const RAW: &str = r#"
+        [HOTEL]
+        name = "Hilton"
+        "quoted key" = "tab\there"
+        raw = 'C:\temp'
+        note = """
+            multi
+            line
+            """
+        codes = ["A", "B"]
+        address = { street = "Main", no = 1 }
+        count = 3
+
+        [HOTEL.rooms]
+        | code | "quoted" | a\|b |
+        "#;
+
+    #[test]
+    fn borrows_what_needs_no_unescaping() {
+        let ion = IonRef::parse(RAW).unwrap();
+        let hotel = ion.get("HOTEL").unwrap();
+
+        assert!(matches!(hotel.get("name"), Some(ValueRef::String(Cow::Borrowed("Hilton")))));
+        assert!(matches!(hotel.get("raw"), Some(ValueRef::String(Cow::Borrowed("C:\\temp")))));
+        assert!(matches!(hotel.get("quoted key"), Some(ValueRef::String(Cow::Owned(_)))));
+        assert_eq!(Some("tab\there"), hotel.get("quoted key").and_then(ValueRef::as_str));
+        assert_eq!(Some("multi\nline"), hotel.get("note").and_then(ValueRef::as_str));
+        assert_eq!(Some("Main"), hotel.get("address").and_then(|a| a.get("street")).and_then(ValueRef::as_str));
+        assert_eq!(Some(3), hotel.get("count").and_then(ValueRef::as_integer));
+        assert!(hotel.dictionary.keys().all(|k| matches!(*k, Cow::Borrowed(_))));
+
+        let rooms = ion.get("HOTEL.rooms").unwrap();
+        assert!(matches!(rooms.rows[0][0], ValueRef::String(Cow::Borrowed("code"))));
+        assert!(matches!(rooms.rows[0][1], ValueRef::String(Cow::Borrowed("quoted"))));
+        assert!(matches!(rooms.rows[0][2], ValueRef::String(Cow::Owned(ref s)) if s == "a|b"));
+        assert!(ion.iter().all(|(name, _)| matches!(*name, Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn into_owned_matches_owned_parse() {
+        let owned: Ion = RAW.parse().unwrap();
+        let borrowed = IonRef::parse(RAW).unwrap().into_owned();
+
+        for (name, section) in owned.iter() {
+            assert_eq!(Some(section), borrowed.get(name));
+        }
+        assert_eq!(owned.iter().count(), borrowed.iter().count());
+        assert_eq!(Value::new_string("Hilton"), ValueRef::String(Cow::Borrowed("Hilton")).into_owned());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(IonRef::parse("[A]\nx = [1").is_err());
+        assert!(IonRef::parse("[A]\n#include \"b.ion\"").is_err());
+    }
+}
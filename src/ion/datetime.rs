@@ -0,0 +1,302 @@
+use std::{error, fmt};
+use std::str::FromStr;
+
+/// A calendar date, written as `2026-10-17`
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+/// A local time of day, written as `14:30:00` or `14:30:00.250`
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+}
+
+/// A date with a time and an optional UTC offset, written as `2026-10-17T14:30:00+02:00`,
+/// `2026-10-17T12:30:00Z` or, without an offset, `2026-10-17T14:30:00`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DateTime {
+    date: Date,
+    time: Time,
+    offset: Option<i16>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ParseDateTimeError;
+
+impl Date {
+    /// Returns `None` if the date does not exist
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        if year > 9999 || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl Time {
+    /// Returns `None` if any of the components is out of range
+    pub fn new(hour: u8, minute: u8, second: u8, nanosecond: u32) -> Option<Time> {
+        if hour > 23 || minute > 59 || second > 59 || nanosecond > 999_999_999 {
+            return None;
+        }
+        Some(Time { hour, minute, second, nanosecond })
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+}
+
+impl DateTime {
+    /// `offset` is the offset from UTC in minutes, `None` for a local date time.
+    /// Returns `None` if the offset is not within +/- 23:59
+    pub fn new(date: Date, time: Time, offset: Option<i16>) -> Option<DateTime> {
+        match offset {
+            Some(minutes) if minutes.abs() >= 24 * 60 => None,
+            _ => Some(DateTime { date, time, offset }),
+        }
+    }
+
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    pub fn time(&self) -> Time {
+        self.time
+    }
+
+    /// The offset from UTC in minutes
+    pub fn offset(&self) -> Option<i16> {
+        self.offset
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// reads exactly `n` digits from the start of `s`
+fn digits(s: &str, n: usize) -> Result<(u32, &str), ParseDateTimeError> {
+    if s.len() < n || !s.as_bytes()[..n].iter().all(u8::is_ascii_digit) {
+        return Err(ParseDateTimeError);
+    }
+    let value = s[..n].parse().map_err(|_| ParseDateTimeError)?;
+    Ok((value, &s[n..]))
+}
+
+fn separator(s: &str, sep: char) -> Result<&str, ParseDateTimeError> {
+    if s.starts_with(sep) { Ok(&s[1..]) } else { Err(ParseDateTimeError) }
+}
+
+fn date(s: &str) -> Result<(Date, &str), ParseDateTimeError> {
+    let (year, s) = digits(s, 4)?;
+    let (month, s) = digits(separator(s, '-')?, 2)?;
+    let (day, s) = digits(separator(s, '-')?, 2)?;
+    let date = Date::new(year as u16, month as u8, day as u8).ok_or(ParseDateTimeError)?;
+    Ok((date, s))
+}
+
+fn time(s: &str) -> Result<(Time, &str), ParseDateTimeError> {
+    let (hour, s) = digits(s, 2)?;
+    let (minute, s) = digits(separator(s, ':')?, 2)?;
+    let (second, mut s) = digits(separator(s, ':')?, 2)?;
+
+    let mut nanosecond = 0;
+    if s.starts_with('.') {
+        let len = s[1..].bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 || len > 9 {
+            return Err(ParseDateTimeError);
+        }
+        let (fraction, rest) = digits(&s[1..], len)?;
+        nanosecond = fraction * 10u32.pow(9 - len as u32);
+        s = rest;
+    }
+
+    let time = Time::new(hour as u8, minute as u8, second as u8, nanosecond).ok_or(ParseDateTimeError)?;
+    Ok((time, s))
+}
+
+fn offset(s: &str) -> Result<(Option<i16>, &str), ParseDateTimeError> {
+    let sign = match s.chars().next() {
+        Some('Z') | Some('z') => return Ok((Some(0), &s[1..])),
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Ok((None, s)),
+    };
+    let (hours, s) = digits(&s[1..], 2)?;
+    let (minutes, s) = digits(separator(s, ':')?, 2)?;
+    if hours > 23 || minutes > 59 {
+        return Err(ParseDateTimeError);
+    }
+    Ok((Some(sign * (hours * 60 + minutes) as i16), s))
+}
+
+fn date_time(s: &str) -> Result<(DateTime, &str), ParseDateTimeError> {
+    let (date, s) = date(s)?;
+    let s = separator(s, 'T').or_else(|_| separator(s, 't'))?;
+    let (time, s) = time(s)?;
+    let (offset, s) = offset(s)?;
+    Ok((DateTime { date, time, offset }, s))
+}
+
+fn complete<T>(result: Result<(T, &str), ParseDateTimeError>) -> Result<T, ParseDateTimeError> {
+    match result {
+        Ok((value, "")) => Ok(value),
+        _ => Err(ParseDateTimeError),
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseDateTimeError;
+
+    fn from_str(s: &str) -> Result<Date, ParseDateTimeError> {
+        complete(date(s))
+    }
+}
+
+impl FromStr for Time {
+    type Err = ParseDateTimeError;
+
+    fn from_str(s: &str) -> Result<Time, ParseDateTimeError> {
+        complete(time(s))
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ParseDateTimeError;
+
+    fn from_str(s: &str) -> Result<DateTime, ParseDateTimeError> {
+        complete(date_time(s))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        match self.offset {
+            Some(0) => f.write_str("Z"),
+            Some(minutes) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                write!(f, "{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+impl error::Error for ParseDateTimeError {
+    fn description(&self) -> &str {
+        "invalid date or time"
+    }
+}
+
+impl fmt::Display for ParseDateTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid date or time")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, DateTime, Time};
+
+    #[test]
+    fn date() {
+        let d: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(Date::new(2024, 2, 29), Some(d));
+        assert_eq!("2024-02-29", d.to_string());
+
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2023-13-01".parse::<Date>().is_err());
+        assert!("2023-1-01".parse::<Date>().is_err());
+        assert!("2023-01-01x".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn time() {
+        let t: Time = "14:30:05".parse().unwrap();
+        assert_eq!(Time::new(14, 30, 5, 0), Some(t));
+        assert_eq!("14:30:05", t.to_string());
+
+        let t: Time = "14:30:05.25".parse().unwrap();
+        assert_eq!(250_000_000, t.nanosecond());
+        assert_eq!("14:30:05.25", t.to_string());
+
+        assert!("24:00:00".parse::<Time>().is_err());
+        assert!("14:30".parse::<Time>().is_err());
+        assert!("14:30:00.".parse::<Time>().is_err());
+    }
+
+    #[test]
+    fn date_time() {
+        let dt: DateTime = "2026-10-17T14:30:00+02:00".parse().unwrap();
+        assert_eq!(Some(120), dt.offset());
+        assert_eq!("2026-10-17T14:30:00+02:00", dt.to_string());
+
+        let dt: DateTime = "2026-10-17T14:30:00-09:30".parse().unwrap();
+        assert_eq!(Some(-570), dt.offset());
+        assert_eq!("2026-10-17T14:30:00-09:30", dt.to_string());
+
+        let dt: DateTime = "2026-10-17t12:30:00z".parse().unwrap();
+        assert_eq!("2026-10-17T12:30:00Z", dt.to_string());
+
+        let dt: DateTime = "2026-10-17T12:30:00".parse().unwrap();
+        assert_eq!(None, dt.offset());
+
+        assert!("2026-10-17 12:30:00".parse::<DateTime>().is_err());
+        assert!("2026-10-17T12:30:00+2:00".parse::<DateTime>().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use parser::{infer_cell, is_key_char};
 use {Ion, Section, Value};
 
 use std::fmt;
@@ -16,16 +17,16 @@ impl fmt::Display for Ion {
 impl fmt::Display for Section {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         for (k, v) in &self.dictionary {
-            if v.type_str() == "string" {
-                f.write_fmt(format_args!("{} = \"{}\"\n", k, v))?;
+            if let Value::String(ref s) = *v {
+                f.write_fmt(format_args!("{} = {}\n", Key(k), Quoted(s)))?;
             } else {
-                f.write_fmt(format_args!("{} = {}\n", k, v))?;
+                f.write_fmt(format_args!("{} = {}\n", Key(k), v))?;
             }
         }
 
         for row in &self.rows {
             for cell in row {
-                fmt::Display::fmt(&format!("| {} ", cell), f)?;
+                f.write_fmt(format_args!("| {} ", Cell(cell)))?;
             }
             f.write_str("|\n")?;
         }
@@ -36,10 +37,15 @@ impl fmt::Display for Section {
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match *self {
+            Value::Null => f.write_str("null"),
             Value::String(ref v) => v.fmt(f),
             Value::Integer(ref v) => v.fmt(f),
-            Value::Float(ref v) => v.fmt(f),
+            Value::Float(ref v) => fmt_float(*v, f),
             Value::Boolean(ref v) => v.fmt(f),
+            Value::Date(ref v) => v.fmt(f),
+            Value::Time(ref v) => v.fmt(f),
+            Value::DateTime(ref v) => v.fmt(f),
+            Value::Reference(ref v) => write!(f, "${{{}}}", v),
             Value::Array(ref v) => {
                 f.write_str("[ ")?;
 
@@ -50,10 +56,8 @@ impl fmt::Display for Value {
                     } else {
                         f.write_str(", ")?
                     }
-                    if i.is_string() {
-                        f.write_str("\"")?;
-                        i.fmt(f)?;
-                        f.write_str("\"")?;
+                    if let Value::String(ref s) = *i {
+                        Quoted(s).fmt(f)?;
                     } else {
                         i.fmt(f)?;
                     }
@@ -70,12 +74,10 @@ impl fmt::Display for Value {
                     } else {
                         f.write_str(", ")?
                     }
-                    k.fmt(f)?;
+                    Key(k).fmt(f)?;
                     f.write_str(" = ")?;
-                    if v.type_str() == "string" {
-                        f.write_str("\"")?;
-                        v.fmt(f)?;
-                        f.write_str("\"")?;
+                    if let Value::String(ref s) = *v {
+                        Quoted(s).fmt(f)?;
                     } else {
                         v.fmt(f)?;
                     }
@@ -86,6 +88,121 @@ impl fmt::Display for Value {
     }
 }
 
+/// Displays a string enclosed in `"`, escaping it so that the parser reads back the same string.
+/// Strings containing newlines are displayed as multi-line strings enclosed in `"""`.
+pub struct Quoted<'a>(pub &'a str);
+
+/// Displays a key as is, or enclosed in `"` if it contains characters not allowed in bare keys
+pub struct Key<'a>(pub &'a str);
+
+impl<'a> fmt::Display for Quoted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        if self.0.contains('\n') {
+            f.write_str("\"\"\"\n")?;
+            write_escaped(self.0, true, f)?;
+            f.write_str("\n\"\"\"")
+        } else {
+            f.write_str("\"")?;
+            write_escaped(self.0, false, f)?;
+            f.write_str("\"")
+        }
+    }
+}
+
+impl<'a> fmt::Display for Key<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        if !self.0.is_empty() && self.0.chars().all(is_key_char) {
+            f.write_str(self.0)
+        } else {
+            f.write_str("\"")?;
+            write_escaped(self.0, false, f)?;
+            f.write_str("\"")
+        }
+    }
+}
+
+/// Displays a table cell so that the parser reads back the same value. Strings are displayed
+/// as they are with `|` escaped as `\|`, or enclosed in `"` and escaped when they start or end
+/// with whitespace, start with `"` or `#` or contain control characters. Other values are
+/// displayed as in dictionary values with `|` escaped.
+pub struct Cell<'a>(pub &'a Value);
+
+/// Displays a table cell like `Cell`, also enclosing strings in `"` that
+/// `Parser::with_cell_type_inference` would read as another value, like `1`, `true` or an
+/// empty string
+pub struct InferredCell<'a>(pub &'a Value);
+
+impl<'a> fmt::Display for Cell<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write_cell(self.0, false, f)
+    }
+}
+
+impl<'a> fmt::Display for InferredCell<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write_cell(self.0, true, f)
+    }
+}
+
+fn write_cell(value: &Value, infer_cells: bool, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    let s = match *value {
+        Value::String(ref s) => s,
+        ref v => return write_pipes_escaped(&v.to_string(), f),
+    };
+
+    let quoted = s.starts_with(['"', '#'])
+        || s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace)
+        || s.chars().any(char::is_control)
+        || (infer_cells && infer_cell(s).is_some());
+    if quoted {
+        f.write_str("\"")?;
+        write_escaped(s, false, f)?;
+        f.write_str("\"")
+    } else {
+        write_pipes_escaped(s, f)
+    }
+}
+
+fn write_pipes_escaped(s: &str, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    if s.contains('|') {
+        f.write_str(&s.replace('|', "\\|"))
+    } else {
+        f.write_str(s)
+    }
+}
+
+fn write_escaped(s: &str, multi_line: bool, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    let mut start = 0;
+    for (i, ch) in s.char_indices() {
+        if ch != '"' && ch != '\\' && !ch.is_control() { continue }
+        if ch == '\n' && multi_line { continue }
+        f.write_str(&s[start..i])?;
+        match ch {
+            '"'  => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            _    => f.write_fmt(format_args!("\\u{{{:x}}}", ch as u32))?,
+        }
+        start = i + ch.len_utf8();
+    }
+    f.write_str(&s[start..])
+}
+
+// floats are always written with a fraction or an exponent and with enough digits for the parser
+// to read back the same value as a float
+fn fmt_float(v: f64, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    if v.is_nan() {
+        f.write_str(if v.is_sign_negative() { "-nan" } else { "nan" })
+    } else if v.is_infinite() {
+        f.write_str(if v < 0.0 { "-inf" } else { "inf" })
+    } else {
+        fmt::Debug::fmt(&v, f)
+    }
+}
+
 // impl fmt::Display for super::Error {
 //     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 //         fmt::Debug::fmt(self, f)
@@ -1,4 +1,4 @@
-use ion::Value;
+use ion::{Date, DateTime, ParseDateTimeError, Time, Value};
 
 pub trait FromIon<T>: Sized {
     type Err;
@@ -12,16 +12,15 @@ impl FromIon<Value> for String {
     }
 }
 
-impl FromIon<Value> for Option<String> {
-    type Err = ();
+/// `null` and empty strings are converted to `None`
+impl<T: FromIon<Value>> FromIon<Value> for Option<T> {
+    type Err = T::Err;
     fn from_ion(value: &Value) -> Result<Self, Self::Err> {
-        value.as_string()
-            .map(|s| if s.is_empty() {
-                None
-            } else {
-                Some(s.to_owned())
-            })
-            .ok_or(())
+        match *value {
+            Value::Null => Ok(None),
+            Value::String(ref s) if s.is_empty() => Ok(None),
+            _ => T::from_ion(value).map(Some),
+        }
     }
 }
 
@@ -51,10 +50,27 @@ impl FromIon<Value> for bool {
     }
 }
 
+macro_rules! from_ion_value_date_time_impl {
+    ($($t:ident)*) => {$(
+        impl FromIon<Value> for $t {
+            type Err = ParseDateTimeError;
+            fn from_ion(value: &Value) -> Result<Self, Self::Err> {
+                match *value {
+                    Value::$t(v) => Ok(v),
+                    Value::String(ref s) => s.parse(),
+                    _ => Err(ParseDateTimeError),
+                }
+            }
+        }
+    )*}
+}
+
+from_ion_value_date_time_impl!{ Date Time DateTime }
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
-    use ion::{FromIon, Section, Value};
+    use ion::{Date, DateTime, FromIon, Section, Time, Value};
 
     #[test]
     fn string() {
@@ -74,6 +90,24 @@ mod tests {
         let v = Value::from_str("").unwrap();
         let os: Option<String> = v.from_ion().unwrap();
         assert_eq!(None, os);
+
+        let os: Option<String> = Value::Null.from_ion().unwrap();
+        assert_eq!(None, os);
+
+        let os: Result<Option<String>, _> = Value::Integer(1).from_ion();
+        assert!(os.is_err());
+    }
+
+    #[test]
+    fn option_generic() {
+        let ou: Option<u32> = Value::Null.from_ion().unwrap();
+        assert_eq!(None, ou);
+
+        let ou: Option<u32> = Value::from_str("16").unwrap().from_ion().unwrap();
+        assert_eq!(Some(16), ou);
+
+        let ob: Option<bool> = Value::Null.from_ion().unwrap();
+        assert_eq!(None, ob);
     }
 
     #[test]
@@ -99,6 +133,23 @@ mod tests {
 
     }
 
+    #[test]
+    fn date_time() {
+        let date = Date::new(2026, 10, 17).unwrap();
+        let d: Date = Value::Date(date).from_ion().unwrap();
+        assert_eq!(date, d);
+        let d: Date = Value::from_str("2026-10-17").unwrap().from_ion().unwrap();
+        assert_eq!(date, d);
+        assert!(Value::Integer(1).from_ion::<Date>().is_err());
+
+        let time = Time::new(14, 30, 0, 0).unwrap();
+        let t: Time = Value::Time(time).from_ion().unwrap();
+        assert_eq!(time, t);
+
+        let dt: DateTime = Value::from_str("2026-10-17T14:30:00Z").unwrap().from_ion().unwrap();
+        assert_eq!(DateTime::new(date, time, Some(0)), Some(dt));
+    }
+
     struct Foo {
         a: u32,
         b: String,
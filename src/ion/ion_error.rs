@@ -1,4 +1,4 @@
-use std::{error, fmt};
+use std::{error, fmt, io};
 use parser::ParserError;
 
 #[derive(Clone, Debug)]
@@ -7,6 +7,12 @@ pub enum IonError {
     MissingValue(String),
     ParseError,
     ParserErrors(Vec<ParserError>),
+    /// A file could not be read, with its path, the kind of the error and its message
+    Io(String, io::ErrorKind, String),
+    /// A reference to a section or key that does not exist
+    UnresolvedReference(String),
+    /// References that refer back to themselves, from the first to the repeated reference
+    CircularReference(Vec<String>),
 }
 
 impl error::Error for IonError {
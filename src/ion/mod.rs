@@ -12,17 +12,25 @@ macro_rules! ion_filtered {
     }
 }
 
+mod borrowed;
+mod datetime;
 mod display;
 mod from_ion;
 mod from_row;
 mod ion_error;
+mod reference;
 mod section;
 mod value;
 
 use std::str;
-use std::collections::BTreeMap;
-use Parser;
+use std::collections::{btree_map, BTreeMap};
+use std::ops::Bound;
+use std::path::Path;
+use {FsResolver, Parser, ParserError, Resolver};
 
+pub use self::borrowed::{IonRef, SectionRef, ValueRef};
+pub use self::datetime::{Date, DateTime, ParseDateTimeError, Time};
+pub use self::display::{Cell, InferredCell, Key, Quoted};
 pub use self::ion_error::IonError;
 pub use self::section::Section;
 pub use self::value::Value;
@@ -43,6 +51,51 @@ impl Ion {
         parser_to_ion(Parser::new_filtered(s, accepted_sections))
     }
 
+    /// Parses `s` like `from_str`, parsing the sections on up to `threads` threads. Returns the
+    /// same `Ion` or the same errors as `from_str`.
+    #[cfg(feature = "parallel")]
+    pub fn from_str_parallel(s: &str, threads: usize) -> Result<Self, IonError> {
+        ::parallel::read(s, threads).map(Ion::new).map_err(IonError::ParserErrors)
+    }
+
+    /// Parses `s` as far as possible, returning every section that could be read together
+    /// with all errors found, see `Parser::with_recovery`
+    pub fn from_str_recovering(s: &str) -> (Self, Vec<ParserError>) {
+        let mut parser = Parser::new(s).with_recovery();
+        let sections = parser.read().unwrap_or_default();
+        (Ion::new(sections), parser.errors)
+    }
+
+    /// Parses `s`, loading the files named by its `#include` directives through `resolver`
+    pub fn from_str_with_resolver(s: &str, resolver: &dyn Resolver) -> Result<Self, IonError> {
+        let mut parser = Parser::new(s);
+        match parser.read_with_resolver(resolver) {
+            Some(ion) => Ok(Ion::new(ion)),
+            None => Err(IonError::ParserErrors(parser.errors)),
+        }
+    }
+
+    /// Reads and parses the file at `path`, its includes are relative to the including file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, IonError> {
+        let path = path.as_ref().to_string_lossy();
+        let resolver = FsResolver::new();
+        let (file, content) = resolver.resolve(&path, None)
+            .map_err(|e| IonError::Io(path.into_owned(), e.kind(), e.to_string()))?;
+
+        let mut parser = Parser::new(&content).with_file_name(file);
+        match parser.read_with_resolver(&resolver) {
+            Some(ion) => Ok(Ion::new(ion)),
+            None => Err(IonError::ParserErrors(parser.errors)),
+        }
+    }
+
+    /// Replaces every `${SECTION.key}` value with a copy of the referenced value and every
+    /// `${SECTION.key}` inside a string with the referenced value's text, `$${` is kept as a
+    /// literal `${`. A reference without a section refers to a key of its own section.
+    pub fn resolve_references(&mut self) -> Result<(), IonError> {
+        reference::resolve(&mut self.sections)
+    }
+
     pub fn get(&self, key: &str) -> Option<&Section> {
         self.sections.get(key)
     }
@@ -59,6 +112,51 @@ impl Ion {
     pub fn iter(&self) -> ::std::collections::btree_map::Iter<String, Section> {
         self.sections.iter()
     }
+
+    /// Looks up a section by the segments of its dotted name, `&["HOTEL", "rooms"]` finds `[HOTEL.rooms]`
+    pub fn section_path(&self, path: &[&str]) -> Option<&Section> {
+        self.get(&path.join("."))
+    }
+
+    /// Iterates the direct sub-sections of the section at `path` in name order, yielding the last
+    /// segment of their name, e.g. `("rooms", ..)` for `[HOTEL.rooms]` when `path` is `&["HOTEL"]`.
+    /// Names that only exist as part of deeper sections, like `rooms` for `[HOTEL.rooms.suite]`,
+    /// are yielded with `None`. An empty `path` yields the top level sections without "root".
+    pub fn sub_sections(&self, path: &[&str]) -> SubSections<'_> {
+        let prefix = if path.is_empty() { String::new() } else { path.join(".") + "." };
+
+        let mut children = BTreeMap::new();
+        let range = self.sections.range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded));
+        for (name, section) in range.take_while(|&(name, _)| name.starts_with(&prefix)) {
+            if path.is_empty() && name == "root" {
+                continue;
+            }
+            let child = &name[prefix.len()..];
+            let segment = child.split('.').next().unwrap_or(child);
+            if segment.is_empty() {
+                continue;
+            }
+            let entry = children.entry(segment).or_insert(None);
+            if segment.len() == child.len() {
+                *entry = Some(section);
+            }
+        }
+
+        SubSections { iter: children.into_iter() }
+    }
+}
+
+/// Iterator over the direct sub-sections of a section, created by `Ion::sub_sections`
+pub struct SubSections<'a> {
+    iter: btree_map::IntoIter<&'a str, Option<&'a Section>>,
+}
+
+impl<'a> Iterator for SubSections<'a> {
+    type Item = (&'a str, Option<&'a Section>);
+
+    fn next(&mut self) -> Option<(&'a str, Option<&'a Section>)> {
+        self.iter.next()
+    }
 }
 
 impl str::FromStr for Ion {
@@ -151,6 +249,65 @@ mod tests {
         assert_eq!(0, rows.len());
     }
 
+    #[test]
+    fn section_path() {
+        let ion = ion!(r#"
+            [HOTEL]
+            name = "Hotel"
+            [HOTEL.rooms]
+            count = 2
+            [HOTEL.rooms.suite]
+            count = 1
+            [HOTEL.restaurant]
+            [HOTELS]
+            [OTHER.rooms]
+        "#);
+
+        let rooms = ion.section_path(&["HOTEL", "rooms"]).unwrap();
+        assert_eq!(Some(&Value::Integer(2)), rooms.get("count"));
+        assert_eq!(Some(rooms), ion.get("HOTEL.rooms"));
+        assert!(ion.section_path(&["HOTEL", "pool"]).is_none());
+
+        let children: Vec<_> = ion.sub_sections(&["HOTEL"]).map(|(name, _)| name).collect();
+        assert_eq!(vec!["restaurant", "rooms"], children);
+
+        let (name, suite) = ion.sub_sections(&["HOTEL", "rooms"]).next().unwrap();
+        assert_eq!("suite", name);
+        assert_eq!(Some(&Value::Integer(1)), suite.unwrap().get("count"));
+
+        let top: Vec<_> = ion.sub_sections(&[]).map(|(name, section)| (name, section.is_some())).collect();
+        assert_eq!(vec![("HOTEL", true), ("HOTELS", true), ("OTHER", false)], top);
+
+        let children: Vec<_> = ion.sub_sections(&["OTHER"]).map(|(name, _)| name).collect();
+        assert_eq!(vec!["rooms"], children);
+
+        assert_eq!(0, ion.sub_sections(&["HOTEL", "rooms", "suite"]).count());
+    }
+
+    #[test]
+    fn implicit_sub_sections() {
+        let ion = ion!(r#"
+            name = "no sections"
+        "#);
+        assert!(ion.get("root").is_some());
+        assert_eq!(0, ion.sub_sections(&[]).count());
+
+        let ion = ion!(r#"
+            [HOTEL.rooms.suite]
+            count = 1
+            [HOTEL-x]
+        "#);
+
+        let top: Vec<_> = ion.sub_sections(&[]).map(|(name, section)| (name, section.is_some())).collect();
+        assert_eq!(vec![("HOTEL", false), ("HOTEL-x", true)], top);
+
+        let children: Vec<_> = ion.sub_sections(&["HOTEL"]).map(|(name, section)| (name, section.is_some())).collect();
+        assert_eq!(vec![("rooms", false)], children);
+
+        let children: Vec<_> = ion.sub_sections(&["HOTEL", "rooms"]).map(|(name, section)| (name, section.is_some())).collect();
+        assert_eq!(vec![("suite", true)], children);
+    }
+
     #[test]
     fn filtered_section() {
         let ion = ion_filtered!(r#"
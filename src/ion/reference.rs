@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use {IonError, Section, Value};
+
+// Resolves `Value::Reference`s and `${SECTION.key}` interpolations in strings, see
+// `Ion::resolve_references`
+pub fn resolve(sections: &mut BTreeMap<String, Section>) -> Result<(), IonError> {
+    let mut resolved = Vec::new();
+    {
+        let sections: &BTreeMap<String, Section> = sections;
+        let mut resolver = Resolver { sections, resolved: BTreeMap::new(), stack: Vec::new() };
+
+        for (name, section) in sections {
+            let mut dictionary = BTreeMap::new();
+            for key in section.dictionary.keys() {
+                dictionary.insert(key.clone(), resolver.entry(name, key)?);
+            }
+
+            let mut rows = Vec::with_capacity(section.rows.len());
+            for row in &section.rows {
+                rows.push(row.iter().map(|cell| resolver.value(name, cell)).collect::<Result<_, _>>()?);
+            }
+            resolved.push((name.clone(), dictionary, rows));
+        }
+    }
+
+    for (name, dictionary, rows) in resolved {
+        let section = sections.get_mut(&name).expect("resolved a missing section");
+        section.dictionary = dictionary;
+        section.rows = rows;
+    }
+    Ok(())
+}
+
+struct Resolver<'a> {
+    sections: &'a BTreeMap<String, Section>,
+    // resolved dictionary entries by section and key
+    resolved: BTreeMap<(&'a str, &'a str), Value>,
+    // the references being resolved, to detect cycles
+    stack: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn entry(&mut self, section: &'a str, key: &'a str) -> Result<Value, IonError> {
+        if let Some(value) = self.resolved.get(&(section, key)) {
+            return Ok(value.clone());
+        }
+
+        let name = format!("{}.{}", section, key);
+        if let Some(pos) = self.stack.iter().position(|n| *n == name) {
+            let mut cycle = self.stack[pos..].to_vec();
+            cycle.push(name);
+            return Err(IonError::CircularReference(cycle));
+        }
+
+        let value = &self.sections[section].dictionary[key];
+        self.stack.push(name);
+        let resolved = self.value(section, value)?;
+        self.stack.pop();
+
+        self.resolved.insert((section, key), resolved.clone());
+        Ok(resolved)
+    }
+
+    // `section` is the section `value` is in, the default for references without a section
+    fn value(&mut self, section: &'a str, value: &'a Value) -> Result<Value, IonError> {
+        Ok(match *value {
+            Value::Reference(ref reference) => self.reference(section, reference)?,
+            Value::String(ref s) => Value::String(self.interpolate(section, s)?),
+            Value::Array(ref array) => {
+                Value::Array(array.iter().map(|v| self.value(section, v)).collect::<Result<_, _>>()?)
+            },
+            Value::Dictionary(ref dictionary) => {
+                let mut resolved = BTreeMap::new();
+                for (k, v) in dictionary {
+                    resolved.insert(k.clone(), self.value(section, v)?);
+                }
+                Value::Dictionary(resolved)
+            },
+            ref v => v.clone(),
+        })
+    }
+
+    // `SECTION.key` is split at the '.' after the longest section name it starts with that has
+    // the rest as a key, as both section names and quoted keys may contain dots. A reference
+    // that names no such section and key refers to a key of `section`.
+    fn reference(&mut self, section: &'a str, reference: &'a str) -> Result<Value, IonError> {
+        let dots = reference.rmatch_indices('.').map(|(dot, _)| (&reference[..dot], &reference[dot + 1..]));
+        for (target, key) in dots.chain(Some((section, reference))) {
+            if let Some((target, s)) = self.sections.get_key_value(target) {
+                if let Some((key, _)) = s.dictionary.get_key_value(key) {
+                    return self.entry(target, key);
+                }
+            }
+        }
+        Err(IonError::UnresolvedReference(reference.to_owned()))
+    }
+
+    // replaces `${SECTION.key}` with the referenced value and `$${` with a literal `${`,
+    // a `${` without a closing `}` is kept as it is
+    fn interpolate(&mut self, section: &'a str, s: &'a str) -> Result<String, IonError> {
+        if !s.contains("${") {
+            return Ok(s.to_owned());
+        }
+
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                out.push_str(&rest[..start - 1]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+
+            out.push_str(&rest[..start]);
+            match self.reference(section, rest[start + 2..end].trim())? {
+                Value::String(s) => out.push_str(&s),
+                v => out.push_str(&v.to_string()),
+            }
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Ion, IonError, Value};
+
+    #[test]
+    fn resolve_references() {
+        let mut ion = ion!(r#"
+            [COMMON]
+            base = "https://example.com"
+            currency = "EUR"
+            port = 8080
+
+            [HOTEL]
+            code = "H1"
+            url = "${COMMON.base}:${COMMON.port}/hotels/${code}"
+            currency = ${COMMON.currency}
+            prices = [${COMMON.port}, 1]
+            price = "$${not.a.reference} ${unclosed"
+
+            [HOTEL.rooms]
+            currency = ${HOTEL.currency}
+            |${HOTEL.code}|${HOTEL.code}-1|
+        "#);
+        ion.resolve_references().unwrap();
+
+        let hotel = ion.get("HOTEL").unwrap();
+        assert_eq!(Some("https://example.com:8080/hotels/H1"), hotel.get("url").and_then(Value::as_str));
+        assert_eq!(Some("EUR"), hotel.get("currency").and_then(Value::as_str));
+        assert_eq!(Some(&Value::Array(vec![Value::Integer(8080), Value::Integer(1)])), hotel.get("prices"));
+        assert_eq!(Some("${not.a.reference} ${unclosed"), hotel.get("price").and_then(Value::as_str));
+
+        let rooms = ion.get("HOTEL.rooms").unwrap();
+        assert_eq!(Some("EUR"), rooms.get("currency").and_then(Value::as_str));
+        assert_eq!(vec![Value::new_string("H1"), Value::new_string("H1-1")], rooms.rows[0]);
+    }
+
+    #[test]
+    fn reference_errors() {
+        let mut ion: Ion = "[A]\nx = ${B.y}\n[B]\nz = 1".parse().unwrap();
+        match ion.resolve_references() {
+            Err(IonError::UnresolvedReference(r)) => assert_eq!("B.y", r),
+            e => panic!("unexpected {:?}", e),
+        }
+
+        let mut ion: Ion = "[A]\nx = \"${B.y}\"\n[B]\ny = ${A.x}".parse().unwrap();
+        match ion.resolve_references() {
+            Err(IonError::CircularReference(cycle)) => assert_eq!(vec!["A.x", "B.y", "A.x"], cycle),
+            e => panic!("unexpected {:?}", e),
+        }
+
+        let mut ion: Ion = "[A]\nx = ${x}".parse().unwrap();
+        assert!(ion.resolve_references().is_err());
+    }
+
+    #[test]
+    fn dotted_names() {
+        let mut ion = ion!(r#"
+            [A]
+            "b.c" = 1
+            own = ${b.c}
+
+            [A.b]
+            "c.d" = 2
+            e = 3
+
+            [B]
+            x = ${A.b.c}
+            y = ${A.b.c.d}
+            z = ${A.b.e}
+        "#);
+        ion.resolve_references().unwrap();
+
+        assert_eq!(Some(&Value::Integer(1)), ion.get("A").unwrap().get("own"));
+        let b = ion.get("B").unwrap();
+        assert_eq!(Some(&Value::Integer(1)), b.get("x"));
+        assert_eq!(Some(&Value::Integer(2)), b.get("y"));
+        assert_eq!(Some(&Value::Integer(3)), b.get("z"));
+    }
+}
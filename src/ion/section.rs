@@ -1,4 +1,5 @@
 use std::vec;
+use parser::infer_cell;
 use {Dictionary, FromIon, IonError, Row, Value};
 
 #[derive(Debug, PartialEq)]
@@ -29,19 +30,37 @@ impl Section {
     }
 
     pub fn rows_without_header(&self) -> &[Row] {
-        if self.rows.len() > 1 {
-            let row = &self.rows[1];
-            if row.first().map_or(false, |v| match v {
-                Value::String(s) => !s.is_empty() && s.chars().all(|c| c == '-'),
-                _ => false,
-            }) {
-                return &self.rows[2..];
-            }
+        if self.has_header() {
+            return &self.rows[2..];
         }
 
         &self.rows
     }
 
+    // the first row is a header when the second one is a `|---|` separator
+    fn has_header(&self) -> bool {
+        self.rows.len() > 1 && self.rows[1].first().map_or(false, |v| match v {
+            Value::String(s) => !s.is_empty() && s.chars().all(|c| c == '-'),
+            _ => false,
+        })
+    }
+
+    /// Converts the string cells of all rows but the header that are integers, floats,
+    /// booleans or `null` into those values, as written in dictionary values.
+    /// Empty cells become `Value::Null`, see `Parser::with_cell_type_inference`.
+    pub fn infer_types(&mut self) {
+        let skip = if self.has_header() { 1 } else { 0 };
+        for cell in self.rows.iter_mut().skip(skip).flat_map(|row| row.iter_mut()) {
+            let inferred = match *cell {
+                Value::String(ref s) => infer_cell(s),
+                _ => None,
+            };
+            if let Some(value) = inferred {
+                *cell = value;
+            }
+        }
+    }
+
     pub fn parse<F: FromIon<Section>>(&self) -> Result<F, F::Err> {
         F::from_ion(self)
     }
@@ -362,4 +381,50 @@ mod tests {
             assert_eq!(0, section.rows_without_header().len())
         }
     }
+
+    mod infer_types {
+        use Value;
+
+        #[test]
+        fn it_converts_literals_and_keeps_other_cells() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                | name | 1     | price | true | null |
+                |------|-------|-------|------|------|
+                | a    | 1_000 | 2.5   | true |      |
+                | b    | 0x1F  | -1e3  | no   | null |
+                | 12ab | +7    | inf   | trueish | 1 2 |
+                "#
+            );
+
+            let mut section = ion.remove("FOO").unwrap();
+            section.infer_types();
+
+            let rows = section.rows_without_header();
+            assert_eq!(vec![Value::new_string("a"), Value::Integer(1000), Value::Float(2.5), Value::Boolean(true), Value::Null], rows[0]);
+            assert_eq!(vec![Value::new_string("b"), Value::Integer(31), Value::Float(-1000.0), Value::new_string("no"), Value::Null], rows[1]);
+            assert_eq!(vec![Value::new_string("12ab"), Value::Integer(7), Value::Float(::std::f64::INFINITY),
+                            Value::new_string("trueish"), Value::new_string("1 2")], rows[2]);
+            assert_eq!(vec![Value::new_string("name"), Value::new_string("1"), Value::new_string("price"),
+                            Value::new_string("true"), Value::new_string("null")], section.rows[0]);
+        }
+
+        #[test]
+        fn it_converts_the_first_row_without_a_separator() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                | 1 | true |
+                | 2 | -    |
+                "#
+            );
+
+            let mut section = ion.remove("FOO").unwrap();
+            section.infer_types();
+
+            assert_eq!(vec![Value::Integer(1), Value::Boolean(true)], section.rows[0]);
+            assert_eq!(vec![Value::Integer(2), Value::new_string("-")], section.rows[1]);
+        }
+    }
 }
@@ -1,12 +1,18 @@
 use std::str::FromStr;
-use {Dictionary, FromIon, IonError, Row};
+use {Date, DateTime, Dictionary, FromIon, IonError, Row, Time};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Value {
+    Null,
     String(String),
     Integer(i64),
     Float(f64),
     Boolean(bool),
+    Date(Date),
+    Time(Time),
+    DateTime(DateTime),
+    /// A `${SECTION.key}` reference to another value, see `Ion::resolve_references`
+    Reference(String),
     Array(Row),
     Dictionary(Dictionary),
 }
@@ -26,15 +32,24 @@ impl Value {
 
     pub fn type_str(&self) -> &'static str {
         match *self {
+            Value::Null => "null",
             Value::String(..) => "string",
             Value::Integer(..) => "integer",
             Value::Float(..) => "float",
             Value::Boolean(..) => "boolean",
+            Value::Date(..) => "date",
+            Value::Time(..) => "time",
+            Value::DateTime(..) => "datetime",
+            Value::Reference(..) => "reference",
             Value::Array(..) => "array",
             Value::Dictionary(..) => "dictionary",
         }
     }
 
+    pub fn is_null(&self) -> bool {
+        matches!(*self, Value::Null)
+    }
+
     pub fn as_string(&self) -> Option<&String> {
         match *self {
             Value::String(ref v) => Some(v),
@@ -77,6 +92,27 @@ impl Value {
         }
     }
 
+    pub fn as_date(&self) -> Option<Date> {
+        match *self {
+            Value::Date(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_time(&self) -> Option<Time> {
+        match *self {
+            Value::Time(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_datetime(&self) -> Option<DateTime> {
+        match *self {
+            Value::DateTime(v) => Some(v),
+            _ => None,
+        }
+    }
+
     pub fn as_array(&self) -> Option<&Vec<Value>> {
         match *self {
             Value::Array(ref v) => Some(v),
@@ -9,11 +9,24 @@ extern crate regex;
 use std::collections::BTreeMap;
 
 #[macro_use] mod ion;
+mod index;
+#[cfg(feature = "parallel")]
+mod parallel;
 mod parser;
+mod reader;
+mod renderer;
+mod resolver;
+mod split;
 mod writer;
-pub use parser::{ Parser, ParserError };
+pub use index::IonIndex;
+pub use parser::{ DuplicateKeys, DuplicateSections, Element, Event, EventKind, Events, Parser, ParserError, ParserErrorKind };
+pub use reader::SectionReader;
+pub use renderer::Renderer;
+pub use resolver::{ FsResolver, Resolver };
 pub use writer::Writer;
 
 pub type Dictionary = BTreeMap<String, Value>;
 pub use ion::{Ion, IonError, FromIon, Section, Value};
+pub use ion::{Date, DateTime, ParseDateTimeError, SubSections, Time};
+pub use ion::{IonRef, SectionRef, ValueRef};
 pub type Row = Vec<Value>;
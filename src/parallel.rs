@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::thread;
+use parser::offset_errors;
+use split::section_starts;
+use {Parser, ParserError, Section};
+
+/// Reads `input` like `Parser::new(input).read()`, parsing the sections on up to `threads`
+/// threads. The sections and the errors, with their positions, are the same as those of the
+/// sequential parser: should a part parse differently on its own, the whole input is parsed
+/// again sequentially.
+pub fn read(input: &str, threads: usize) -> Result<BTreeMap<String, Section>, Vec<ParserError>> {
+    let mut bounds = vec![0];
+    bounds.extend(section_starts(input).into_iter().map(|(start, _)| start));
+    bounds.push(input.len());
+    let chunks: Vec<Range<usize>> = bounds.windows(2).map(|w| w[0]..w[1]).collect();
+
+    let results = parse_chunks(input, &chunks, threads);
+
+    let mut sections = BTreeMap::new();
+    for (i, (chunk, result)) in chunks.iter().zip(results).enumerate() {
+        let map = match result {
+            Ok(map) => map,
+            Err(mut errors) => {
+                offset_errors(&mut errors, chunk.start, input[..chunk.start].matches('\n').count());
+                return Err(errors);
+            },
+        };
+        // the elements before the first section, which only make a "root" section without sections
+        if i == 0 {
+            if chunks.len() == 1 { return Ok(map) }
+            if map.len() > 1 || map.keys().any(|name| name != "root") { return read_sequential(input) }
+            continue;
+        }
+        if map.len() != 1 { return read_sequential(input) }
+        sections.extend(map);
+    }
+    Ok(sections)
+}
+
+// parses the chunks on up to `threads` threads, each taking a run of consecutive chunks of
+// about the same size, and returns the results in the order of the chunks. The `parallel`
+// feature needs Rust 1.63 for scoped threads, above the 1.51 of the rest of the crate.
+#[allow(clippy::incompatible_msrv)]
+fn parse_chunks(input: &str, chunks: &[Range<usize>], threads: usize) -> Vec<Result<BTreeMap<String, Section>, Vec<ParserError>>> {
+    let size = input.len() / threads.max(1) + 1;
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for (i, chunk) in chunks.iter().enumerate() {
+        if chunk.end - chunks[start].start >= size || i + 1 == chunks.len() {
+            runs.push(&chunks[start..=i]);
+            start = i + 1;
+        }
+    }
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = runs.into_iter()
+            .map(|run| scope.spawn(move || run.iter().map(|chunk| parse(&input[chunk.clone()])).collect::<Vec<_>>()))
+            .collect();
+
+        handles.into_iter()
+            .flat_map(|handle| handle.join().expect("parser thread panicked"))
+            .collect()
+    })
+}
+
+fn parse(chunk: &str) -> Result<BTreeMap<String, Section>, Vec<ParserError>> {
+    let mut parser = Parser::new(chunk);
+    parser.read().ok_or(parser.errors)
+}
+
+fn read_sequential(input: &str) -> Result<BTreeMap<String, Section>, Vec<ParserError>> {
+    let mut parser = Parser::new(input);
+    parser.read().ok_or(parser.errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+    use Ion;
+
+    fn assert_same(input: &str) {
+        let sequential = input.parse::<Ion>();
+        for threads in 1..5 {
+            match (&sequential, Ion::from_str_parallel(input, threads)) {
+                (Ok(sequential), Ok(parallel)) => {
+                    assert_eq!(sequential.iter().collect::<Vec<_>>(), parallel.iter().collect::<Vec<_>>());
+                },
+                (Err(sequential), Err(parallel)) => assert_eq!(format!("{:?}", sequential), format!("{:?}", parallel)),
+                (s, p) => panic!("sequential {:?}, parallel {:?}", s, p),
+            }
+        }
+    }
+
+    #[test]
+    fn same_as_sequential() {
+        let mut input = String::from("x = 0\n");
+        for i in 0..200 {
+            let _ = write!(input, "[S{}]\nname = \"s{}\"\ntext = \"\"\"\n[not a section]\n\"\"\"\n| a | {} |\n", i % 150, i, i);
+        }
+        assert_same(&input);
+        assert_same("x = 1\ny = 2\n");
+        assert_same("");
+        assert_same("[A]\nx = 1 [B]\ny = 2\n");
+        assert_same("[A]\nx = 1\n[B]\ny = [1\n[C]\nz = ?\n[D]\nw = ?\n");
+        assert_same("x = ?\n[A]\nx = 1\n");
+        assert_same("[A]\nx = 1\n[B]\n\ny = [1, ?]\n[C]\nz = #\n");
+    }
+}
@@ -1,69 +1,272 @@
-use std::collections::BTreeMap;
-use std::iter::Peekable;
-use std::{ error, fmt, str };
-use { Section, Value };
-
+use std::borrow::{Borrow, Cow};
+use std::collections::{btree_map, BTreeMap};
+use std::marker::PhantomData;
+use std::{ error, fmt, mem, str };
+use split::{Line, Splitter};
+use { DateTime, Resolver, Section, SectionRef, Value, ValueRef };
+
+/// An element of ion text as yielded by `Parser`'s `Iterator`, see `Parser::events` for the
+/// elements with their positions
 #[derive(Debug, PartialEq)]
 pub enum Element {
     Section(String),
     Row(Vec<Value>),
     Entry(String, Value),
-    Comment(String)
+    Comment(String),
+    Include(String)
+}
+
+// an `Element` as built by `B`
+enum Parsed<'a, B: Output> {
+    Section(B::Key),
+    Row(Vec<B::Value>),
+    Entry(B::Key, B::Value),
+    Comment(&'a str),
+    Include(Cow<'a, str>),
+}
+
+impl<'a> Parsed<'a, Owned> {
+    fn into_element(self) -> Element {
+        match self {
+            Parsed::Section(name) => Element::Section(name),
+            Parsed::Row(row) => Element::Row(row),
+            Parsed::Entry(key, value) => Element::Entry(key, value),
+            Parsed::Comment(comment) => Element::Comment(comment.to_owned()),
+            Parsed::Include(path) => Element::Include(path.into_owned()),
+        }
+    }
+}
+
+// what the parser builds the sections of, `Owned` builds `Section`s of `Value`s and
+// `Borrowed` builds `SectionRef`s of `ValueRef`s
+trait Output: Sized {
+    type Key: Ord + Borrow<str>;
+    type Value;
+    type Section;
+
+    fn section(capacity: usize) -> Self::Section;
+    fn insert(section: &mut Self::Section, key: Self::Key, value: Self::Value);
+    fn push_row(section: &mut Self::Section, row: Vec<Self::Value>);
+    // appends the rows of `other` and replaces the keys it defines again
+    fn merge(section: &mut Self::Section, other: Self::Section);
+    fn root() -> Self::Key;
+    // reads the elements of an included file into `sections`, false if these sections cannot
+    // hold them
+    fn read_included(parser: &mut Parser, sections: &mut Sections<Self>, resolver: &dyn Resolver, including: &mut Vec<String>) -> bool;
+}
+
+// how the parser builds values from the input
+trait Build<'a>: Output {
+    fn key(key: Cow<'a, str>) -> Self::Key;
+    fn string(s: Cow<'a, str>) -> Self::Value;
+    fn reference(name: Cow<'a, str>) -> Self::Value;
+    // values without strings
+    fn scalar(value: Value) -> Self::Value;
+    fn array(values: Vec<Self::Value>) -> Self::Value;
+    fn dictionary(map: BTreeMap<Self::Key, Self::Value>) -> Self::Value;
+}
+
+struct Owned;
+
+impl Output for Owned {
+    type Key = String;
+    type Value = Value;
+    type Section = Section;
+
+    fn section(capacity: usize) -> Section { Section::with_capacity(capacity) }
+    fn insert(section: &mut Section, key: String, value: Value) { section.dictionary.insert(key, value); }
+    fn push_row(section: &mut Section, row: Vec<Value>) { section.rows.push(row) }
+    fn merge(section: &mut Section, other: Section) {
+        section.dictionary.extend(other.dictionary);
+        section.rows.extend(other.rows);
+    }
+    fn root() -> String { "root".to_owned() }
+
+    fn read_included(parser: &mut Parser, sections: &mut Sections<Owned>, resolver: &dyn Resolver, including: &mut Vec<String>) -> bool {
+        parser.read_into(sections, Some(resolver), including);
+        true
+    }
+}
+
+impl<'a> Build<'a> for Owned {
+    fn key(key: Cow<'a, str>) -> String { key.into_owned() }
+    fn string(s: Cow<'a, str>) -> Value { Value::String(s.into_owned()) }
+    fn reference(name: Cow<'a, str>) -> Value { Value::Reference(name.into_owned()) }
+    fn scalar(value: Value) -> Value { value }
+    fn array(values: Vec<Value>) -> Value { Value::Array(values) }
+    fn dictionary(map: BTreeMap<String, Value>) -> Value { Value::Dictionary(map) }
+}
+
+struct Borrowed<'a>(PhantomData<&'a str>);
+
+impl<'a> Output for Borrowed<'a> {
+    type Key = Cow<'a, str>;
+    type Value = ValueRef<'a>;
+    type Section = SectionRef<'a>;
+
+    fn section(capacity: usize) -> SectionRef<'a> { SectionRef::with_capacity(capacity) }
+    fn insert(section: &mut SectionRef<'a>, key: Cow<'a, str>, value: ValueRef<'a>) { section.dictionary.insert(key, value); }
+    fn push_row(section: &mut SectionRef<'a>, row: Vec<ValueRef<'a>>) { section.rows.push(row) }
+    fn merge(section: &mut SectionRef<'a>, other: SectionRef<'a>) {
+        section.dictionary.extend(other.dictionary);
+        section.rows.extend(other.rows);
+    }
+    fn root() -> Cow<'a, str> { Cow::Borrowed("root") }
+
+    // the included input does not live as long as the sections borrowing from it
+    fn read_included(_: &mut Parser, _: &mut Sections<Borrowed<'a>>, _: &dyn Resolver, _: &mut Vec<String>) -> bool {
+        false
+    }
+}
+
+impl<'a> Build<'a> for Borrowed<'a> {
+    fn key(key: Cow<'a, str>) -> Cow<'a, str> { key }
+    fn string(s: Cow<'a, str>) -> ValueRef<'a> { ValueRef::String(s) }
+    fn reference(name: Cow<'a, str>) -> ValueRef<'a> { ValueRef::Reference(name) }
+    fn scalar(value: Value) -> ValueRef<'a> { ValueRef::from(value) }
+    fn array(values: Vec<ValueRef<'a>>) -> ValueRef<'a> { ValueRef::Array(values) }
+    fn dictionary(map: BTreeMap<Cow<'a, str>, ValueRef<'a>>) -> ValueRef<'a> { ValueRef::Dictionary(map) }
+}
+
+/// An element of ion text with its position in the input, yielded by `Parser::events`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Event<'a> {
+    /// The low byte of the element.
+    pub lo: usize,
+    /// One byte beyond the element, the line end is not part of it.
+    pub hi: usize,
+    /// The line of `lo`, starting at 1.
+    pub line: usize,
+    /// The column of `lo` in characters, starting at 1.
+    pub col: usize,
+    pub kind: EventKind<'a>,
+}
+
+/// What an `Event` is, the strings borrow from the input unless they contain escape sequences
+/// or are multi-line strings with indentation or carriage returns to remove
+#[derive(Debug, PartialEq, Clone)]
+pub enum EventKind<'a> {
+    /// A `[SECTION]` header
+    Section(Cow<'a, str>),
+    /// A `key = value` entry
+    Entry(Cow<'a, str>, ValueRef<'a>),
+    /// A table row, separator rows like `|---|` included
+    Row(Vec<ValueRef<'a>>),
+    /// A comment without the `#` and the line end
+    Comment(&'a str),
+    /// An `#include "path"` directive, which is not resolved
+    Include(Cow<'a, str>),
+    /// A line of nothing but whitespace
+    BlankLine,
+}
+
+/// The elements of ion text with their positions, see `Parser::events`
+pub struct Events<'p, 'a: 'p> {
+    parser: &'p mut Parser<'a>,
+    // the line and the byte offset of the start of the line of the last event
+    line: usize,
+    line_start: usize,
+}
+
+impl<'p, 'a> Events<'p, 'a> {
+    fn event(&mut self, lo: usize, hi: usize, kind: EventKind<'a>) -> Event<'a> {
+        let input = self.parser.input;
+        let between = &input[self.line_start..lo];
+        if let Some(n) = between.rfind('\n') {
+            self.line += between.matches('\n').count();
+            self.line_start += n + 1;
+        }
+        let col = input[self.line_start..lo].chars().count() + 1;
+        let hi = lo + input[lo..hi.max(lo)].trim_end().len();
+        Event { lo, hi, line: self.line, col, kind }
+    }
+
+    // the length of the rest of the line from `pos` with its line end, if it is blank. The
+    // last line may have no line end but is only blank if it is not empty.
+    fn blank_line(&self, pos: usize) -> Option<usize> {
+        let rest = &self.parser.input[pos..];
+        let end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+        if end > 0 && rest[..end].bytes().all(|b| matches!(b, b' ' | b'\t' | b'\r' | b'\n')) { Some(end) } else { None }
+    }
+}
+
+impl<'p, 'a> Iterator for Events<'p, 'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        let pos = self.parser.pos;
+        let at_line_start = pos == 0 || self.parser.input.as_bytes()[pos - 1] == b'\n';
+        if let Some(len) = self.blank_line(pos) {
+            self.parser.pos += len;
+            if at_line_start {
+                return Some(self.event(pos, pos, EventKind::BlankLine));
+            }
+            // the line end of the previous element
+            return self.next();
+        }
+
+        let kind = match self.parser.next_element::<Borrowed<'a>>()? {
+            Parsed::Section(name) => EventKind::Section(name),
+            Parsed::Entry(key, value) => EventKind::Entry(key, value),
+            Parsed::Row(row) => EventKind::Row(row),
+            Parsed::Comment(comment) => EventKind::Comment(comment.trim_end_matches(['\r', '\n'])),
+            Parsed::Include(path) => EventKind::Include(path),
+        };
+        let (lo, hi) = self.parser.span;
+        Some(self.event(lo, hi, kind))
+    }
+}
+
+/// What `Parser::read` does with a section that is defined more than once
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DuplicateSections {
+    /// Reports a `ParserError` at the second definition
+    Error,
+    KeepFirst,
+    KeepLast,
+    /// Merges the dictionaries, later keys replace earlier ones, and appends the rows
+    Merge,
+}
+
+/// What the parser does with a key that is defined more than once in a section or an inline
+/// dictionary, the last definition is kept either way
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DuplicateKeys {
+    Ignore,
+    /// Records a `ParserError` in `Parser::warnings`
+    Warn,
+    /// Reports a `ParserError` at the second definition
+    Error,
 }
 
 pub struct Parser<'a> {
     input: &'a str,
-    cur: Peekable<str::CharIndices<'a>>,
+    // byte offset of the next character
+    pos: usize,
     pub errors: Vec<ParserError>,
+    /// Problems that do not fail the parsing, like duplicate keys with `DuplicateKeys::Warn`
+    pub warnings: Vec<ParserError>,
     accepted_sections: Option<Vec<&'a str>>,
     section_capacity: usize,
     row_capacity: usize,
     array_capacity: usize,
-}
-
-macro_rules! some {
-    ($expr:expr) => ({
-        let ret = $expr;
-        if let Some(value) = ret {
-            value
-        } else {
-            return None;
-        }
-    })
+    file: Option<String>,
+    duplicate_sections: Option<DuplicateSections>,
+    duplicate_keys: DuplicateKeys,
+    recover: bool,
+    strict: bool,
+    infer_cells: bool,
+    // byte range of the last element returned by `next`
+    span: (usize, usize),
+    // the byte, its line and the start of that line last passed to `location`
+    located: (usize, usize, usize),
 }
 
 impl<'a> Iterator for Parser<'a> {
     type Item = Element;
 
     fn next(&mut self) -> Option<Element> {
-        let mut is_section_accepted = true;
-        loop {
-            self.ws();
-            if self.newline() { continue }
-
-            let c = match self.cur.peek() {
-                Some((_, c)) => *c,
-                None => return None,
-            };
-
-            if c == '[' {
-                let name = self.section_name();
-                match self.is_section_accepted(&name) {
-                    Some(true) => return Some(Element::Section(name)),
-                    Some(false) => is_section_accepted = false,
-                    None => return None,
-                };
-            }
-            if !is_section_accepted {
-                self.skip_line();
-                continue;
-            }
-            return match c {
-                '|' => self.row(),
-                '#' => self.comment(),
-                _   => self.entry()
-            };
-        }
+        self.next_element::<Owned>().map(Parsed::into_element)
     }
 }
 
@@ -91,42 +294,182 @@ impl<'a> Parser<'a> {
         self
     }
 
+    /// Names the file the input was read from, the name is reported in the errors and
+    /// passed to the `Resolver` as the including file
+    pub fn with_file_name<S: Into<String>>(mut self, file: S) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Sets what `read` does with duplicate sections. Without a policy the last definition
+    /// is kept, except in a filtered parser which keeps the first and stops reading once it
+    /// has read all accepted sections; with a policy it reads all of the input.
+    pub fn with_duplicate_sections(mut self, duplicate_sections: DuplicateSections) -> Self {
+        self.duplicate_sections = Some(duplicate_sections);
+        self
+    }
+
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Keeps parsing after an error: the rest of the line with the error is skipped, a value
+    /// that runs on into the next lines, like an unterminated array, is skipped up to the line
+    /// of the error. `read` returns the sections read so far, leaving the errors in `errors`
+    pub fn with_recovery(mut self) -> Self {
+        self.recover = true;
+        self
+    }
+
+    /// Reports unterminated strings, line breaks in single-line strings, misspelled booleans
+    /// like `trueish` and anything but a comment after a value or section name on the same line
+    /// as errors, which the default lenient parser accepts or reads as a new entry
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Reads table cells that are integers, floats, booleans or `null` as such instead of as
+    /// strings, empty cells become `Value::Null`, see `Section::infer_types`
+    pub fn with_cell_type_inference(mut self) -> Self {
+        self.infer_cells = true;
+        self
+    }
+
     fn new_filtered_opt(s: &'a str, accepted_sections: Option<Vec<&'a str>>) -> Parser<'a> {
         Parser {
             input: s,
-            cur: s.char_indices().peekable(),
+            pos: 0,
             errors: Vec::new(),
+            warnings: Vec::new(),
             accepted_sections,
             section_capacity: 16,
             row_capacity: 8,
             array_capacity: 2,
+            file: None,
+            duplicate_sections: None,
+            duplicate_keys: DuplicateKeys::Ignore,
+            recover: false,
+            strict: false,
+            infer_cells: false,
+            span: (0, 0),
+            located: (0, 1, 0),
         }
     }
 
-    fn ws(&mut self) {
+    // a parser for an included file, with the same options as this one
+    fn included<'b>(&self, s: &'b str, file: String) -> Parser<'b> where 'a: 'b {
+        Parser {
+            input: s,
+            pos: 0,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            accepted_sections: self.accepted_sections.clone(),
+            section_capacity: self.section_capacity,
+            row_capacity: self.row_capacity,
+            array_capacity: self.array_capacity,
+            file: Some(file),
+            duplicate_sections: self.duplicate_sections,
+            duplicate_keys: self.duplicate_keys,
+            recover: self.recover,
+            strict: self.strict,
+            infer_cells: self.infer_cells,
+            span: (0, 0),
+            located: (0, 1, 0),
+        }
+    }
+
+    /// The elements of the input with their positions, including comments and blank lines.
+    /// Nothing is collected into sections, so duplicate sections and keys are not reported and
+    /// `#include` directives are not resolved. Iteration stops at the first error, see
+    /// `errors`, unless the parser recovers from errors. The events start where the parser
+    /// is, with lines and columns counted from the start of the input.
+    ///
+    /// ```
+    /// use ion::{EventKind, Parser};
+    ///
+    /// let mut parser = Parser::new("[A]\n\nx = 1 # one\n");
+    /// let events: Vec<_> = parser.events().map(|e| (e.line, e.col, e.kind)).collect();
+    /// assert_eq!((1, 1, EventKind::Section("A".into())), events[0]);
+    /// assert_eq!((2, 1, EventKind::BlankLine), events[1]);
+    /// assert!(matches!(events[2], (3, 1, EventKind::Entry(ref key, _)) if key == "x"));
+    /// assert_eq!((3, 7, EventKind::Comment(" one")), events[3]);
+    /// assert!(parser.errors.is_empty());
+    /// ```
+    pub fn events(&mut self) -> Events<'_, 'a> {
+        let before = &self.input[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Events { parser: self, line, line_start }
+    }
+
+    fn next_element<B: Build<'a>>(&mut self) -> Option<Parsed<'a, B>> {
+        let element = self.element();
+        self.span.1 = self.pos();
+        element
+    }
+
+    fn element<B: Build<'a>>(&mut self) -> Option<Parsed<'a, B>> {
+        let mut is_section_accepted = true;
         loop {
-            match self.cur.peek() {
-                Some((_, '\t')) | Some((_, ' ')) => { self.cur.next(); },
-                _ => break
+            self.ws();
+            if self.newline() { continue }
+            self.span.0 = self.pos();
+
+            let c = self.peek()?;
+
+            if c == '[' {
+                let name = self.section_name();
+                match self.is_section_accepted(name) {
+                    Some(true) => {
+                        if self.strict { self.end_of_line(); }
+                        return Some(Parsed::Section(B::key(Cow::Borrowed(name))));
+                    },
+                    Some(false) => is_section_accepted = false,
+                    None => return None,
+                };
+            }
+            if !is_section_accepted {
+                self.skip_to_section();
+                continue;
+            }
+
+            let errors = self.errors.len();
+            let element = match c {
+                '|' => self.row(),
+                '#' => self.comment(),
+                _   => self.entry()
+            };
+            if element.is_none() && self.recover && self.errors.len() > errors {
+                let error = self.errors[errors].lo;
+                self.resync(error);
+                continue;
+            }
+            if self.strict && matches!(element, Some(Parsed::Entry(..)) | Some(Parsed::Include(_))) {
+                self.end_of_line();
             }
+            return element;
+        }
+    }
+
+    fn ws(&mut self) {
+        let bytes = self.input.as_bytes();
+        while matches!(bytes.get(self.pos), Some(b' ') | Some(b'\t')) {
+            self.pos += 1;
         }
     }
 
     fn newline(&mut self) -> bool {
-        match self.cur.peek() {
-            Some((_, '\n')) => {
-                self.cur.next();
+        match self.peek_byte() {
+            Some(b'\n') => {
+                self.pos += 1;
                 true
             },
 
-            Some((_, '\r')) => {
-                self.cur.next();
-
-                match self.cur.peek() {
-                    Some((_, '\n')) => { self.cur.next(); },
-                    _ => (),
-                }
-
+            Some(b'\r') => {
+                self.pos += 1;
+                if self.peek_byte() == Some(b'\n') { self.pos += 1 }
                 true
             },
 
@@ -134,117 +477,244 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // reports anything but whitespace or a comment up to the end of the line and skips it
+    fn end_of_line(&mut self) {
+        self.ws();
+        if matches!(self.peek_byte(), None | Some(b'\n') | Some(b'\r') | Some(b'#')) { return }
+        let lo = self.pos();
+        let rest = self.rest();
+        let hi = lo + rest.find(['\r', '\n']).unwrap_or(rest.len());
+        self.add_error_at(lo, hi, ParserErrorKind::TrailingCharacters, "Unexpected characters at the end of the line");
+        self.skip_line();
+    }
+
+    // skips to the next line that starts a section, or to the end of the input. The `Splitter`
+    // tells a section header from a line of a string or an array that starts with `[`.
+    fn skip_to_section(&mut self) {
+        let mut splitter = Splitter::new();
+        let mut pos = self.pos;
+        for line in self.input[self.pos..].split_inclusive('\n') {
+            if splitter.line(line) == Line::Section && pos > self.pos {
+                self.pos = pos;
+                return;
+            }
+            pos += line.len();
+        }
+        self.pos = self.input.len();
+    }
+
+    // continues after an error at `error` in the element that starts at `span.0`: at the start of
+    // the line of the error if a value ran on into that line, like an unterminated array,
+    // otherwise after the line of the error
+    fn resync(&mut self, error: usize) {
+        let line_start = self.input[..error].rfind('\n').map_or(0, |i| i + 1);
+        if line_start > self.span.0 {
+            self.pos = line_start;
+        } else {
+            self.skip_line();
+        }
+    }
+
     fn skip_line(&mut self) {
-        self.cur.by_ref()
-            .skip_while(|&(_, c)| c != '\n')
-            .next();
+        let rest = self.rest();
+        self.pos += rest.find('\n').map_or(rest.len(), |i| i + 1);
     }
 
-    fn comment(&mut self) -> Option<Element> {
+    fn comment<B: Output>(&mut self) -> Option<Parsed<'a, B>> {
         if !self.eat('#') { return None }
+        if let Some(path) = self.include() { return Some(Parsed::Include(path)) }
+
+        Some(Parsed::Comment(self.slice_to_inc('\n').unwrap_or("")))
+    }
+
+    // `#include "path"` with the `#` already consumed, a comment that merely starts with
+    // "include" is left alone
+    fn include(&mut self) -> Option<Cow<'a, str>> {
+        let rest = self.rest();
+        if !rest.starts_with("include") { return None }
+        let path = rest["include".len()..].trim_start_matches([' ', '\t']);
+        if !path.starts_with('"') || path.starts_with("\"\"\"") { return None }
+
+        self.pos += rest.len() - path.len();
+        self.finish_string()
+    }
 
-        Some(Element::Comment(self.slice_to_inc('\n').unwrap_or("").to_string()))
+    // byte offset of the next character, or the input length at the end of input
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    // the not yet consumed input
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    // the next character, all delimiters are ASCII so only content needs decoding
+    fn peek(&self) -> Option<char> {
+        match self.peek_byte() {
+            Some(b) if b.is_ascii() => Some(b as char),
+            Some(_) => self.rest().chars().next(),
+            None => None,
+        }
+    }
+
+    fn peek_byte(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).cloned()
     }
 
     fn eat(&mut self, ch: char) -> bool {
-        match self.cur.peek() {
-            Some((_, c)) if *c == ch => { self.cur.next(); true }
-            _ => false
+        if self.rest().starts_with(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
         }
     }
 
-    fn section_name(&mut self) -> String {
+    fn section_name(&mut self) -> &'a str {
         self.eat('[');
         self.ws();
-        self.cur.by_ref()
-            .map(|(_, c)| c)
-            .take_while(|c| *c != ']')
-            .collect()
+        let rest = self.rest();
+        let name = &rest[..rest.find(']').unwrap_or(rest.len())];
+        self.pos += name.len();
+        self.eat(']');
+        name
     }
 
-    fn entry(&mut self) -> Option<Element> {
-        let key = some!(self.key_name());
+    fn entry<B: Build<'a>>(&mut self) -> Option<Parsed<'a, B>> {
+        let key = self.key_name()?;
         if !self.keyval_sep() { return None }
-        let val = some!(self.value());
+        let val = self.value_of::<B>()?;
 
-        Some(Element::Entry(key, val))
+        Some(Parsed::Entry(B::key(key), val))
     }
 
-    fn key_name(&mut self) -> Option<String> {
-        self.slice_while(|ch| match ch {
-                'a' ..= 'z' |
-                'A' ..= 'Z' |
-                '0' ..= '9' |
-                '_' | '-' => true,
-                _ => false,
-        }).map(str::to_owned)
+    fn key_name(&mut self) -> Option<Cow<'a, str>> {
+        if self.eat('"') {
+            let lo = self.pos();
+            let hi = self.string_end("\"", true)?;
+            return self.unescaped(lo, hi);
+        }
+
+        let key = self.slice_while(is_key_char).map(Cow::Borrowed);
+        if key.is_none() {
+            self.add_error(ParserErrorKind::InvalidKey, "Cannot read a key, expected a letter, digit, '_', '-' or a quoted key");
+        }
+        key
     }
 
+    #[cfg(test)]
     fn value(&mut self) -> Option<Value> {
+        self.value_of::<Owned>()
+    }
+
+    fn value_of<B: Build<'a>>(&mut self) -> Option<B::Value> {
         self.ws();
         self.newline();
         self.ws();
 
-        match self.cur.peek() {
-            Some((_, '"')) => return self.finish_string(),
-            Some((_, '[')) => return self.finish_array(),
-            Some((_, '{')) => return self.finish_dictionary(),
-            Some((_, ch)) if is_digit(*ch) => self.number(),
-            Some((pos, 't')) |
-            Some((pos, 'f')) => { let pos = *pos; self.boolean(pos) },
+        match self.peek() {
+            Some('"') => self.finish_string().map(B::string),
+            Some('\'') => self.finish_raw_string().map(B::string),
+            Some('[') => self.finish_array::<B>(),
+            Some('{') => self.finish_dictionary::<B>(),
+            Some('$') => self.reference().map(B::reference),
+            _ => self.scalar().map(B::scalar),
+        }
+    }
+
+    // values without strings
+    fn scalar(&mut self) -> Option<Value> {
+        match self.peek() {
+            Some(ch) if is_digit(ch) && self.is_date_or_time() => self.date_time(),
+            Some(ch) if is_digit(ch) || ch == '-' || ch == '+' => self.number(),
+            Some('.') => {
+                self.add_error(ParserErrorKind::InvalidNumber, "Cannot read a number, a leading digit is required before '.'");
+                None
+            },
+            Some('t') |
+            Some('f') => self.boolean(self.pos),
+            Some('i') if self.rest().starts_with("inf") => self.number(),
+            Some('n') if self.rest().starts_with("nan") => self.number(),
+            Some('n') => self.null(),
             _ => {
-                self.add_error("Cannot read a value");
+                self.add_error(ParserErrorKind::InvalidValue, "Cannot read a value");
                 None
             }
         }
     }
 
-    fn finish_array(&mut self) -> Option<Value> {
-        self.cur.next();
+    // `${SECTION.key}`
+    fn reference(&mut self) -> Option<Cow<'a, str>> {
+        if self.rest().starts_with("${") {
+            self.pos += 2;
+            let name = self.slice_while(|c| c != '}' && c != '\n').map(str::trim);
+            if let Some(name) = name.filter(|name| !name.is_empty()) {
+                if self.eat('}') { return Some(Cow::Borrowed(name)) }
+            }
+        }
+        self.add_error(ParserErrorKind::InvalidReference, "Cannot read a reference, expected '${SECTION.key}'");
+        None
+    }
+
+    fn finish_array<B: Build<'a>>(&mut self) -> Option<B::Value> {
+        self.pos += 1;
         let mut row = Vec::with_capacity(self.array_capacity);
 
         loop {
             self.ws();
-            if let Some((_, ch)) = self.cur.peek() {
+            if let Some(ch) = self.peek_byte() {
                 match ch {
-                    ']' => { self.cur.next(); return Some(Value::Array(row)) },
-                    ',' => { self.cur.next(); continue },
+                    b']' => { self.pos += 1; return Some(B::array(row)) },
+                    b',' => { self.pos += 1; continue },
                     _ => {
-                        match self.value() {
+                        match self.value_of::<B>() {
                             Some(v) => row.push(v),
                             None    => break
                         }
                     }
                 }
             } else {
-                self.add_error("Cannot finish an array");
+                self.add_error(ParserErrorKind::UnterminatedArray, "Cannot finish an array");
                 break;
             }
         }
         None
     }
 
-    fn finish_dictionary(&mut self) -> Option<Value> {
-        self.cur.next();
+    fn finish_dictionary<B: Build<'a>>(&mut self) -> Option<B::Value> {
+        self.pos += 1;
         let mut map = BTreeMap::new();
+        // where each key was defined, only tracked when looking for duplicate keys
+        let mut locations = BTreeMap::new();
 
         loop {
             self.ws();
-            if let Some((_, ch)) = self.cur.peek() {
+            if let Some(ch) = self.peek_byte() {
                 match ch {
-                    '}' => { self.cur.next(); return Some(Value::Dictionary(map)) },
-                    ',' => { self.cur.next(); continue },
-                    '\n' => { self.cur.next(); continue },
+                    b'}' => { self.pos += 1; return Some(B::dictionary(map)) },
+                    b',' => { self.pos += 1; continue },
+                    b'\n' => { self.pos += 1; continue },
                     _ => {
-                        match self.entry() {
-                            Some(Element::Entry(k, v)) => map.insert(k, v),
+                        let lo = self.pos();
+                        match self.entry::<B>() {
+                            Some(Parsed::Entry(k, v)) => {
+                                if self.duplicate_keys != DuplicateKeys::Ignore {
+                                    let hi = self.pos();
+                                    let first = locations.insert(k.borrow().to_owned(), self.location(lo));
+                                    if let Some(first) = first {
+                                        self.duplicate_key(lo, hi, k.borrow(), &first);
+                                    }
+                                }
+                                map.insert(k, v);
+                            },
                             None    => break,
                             _ => panic!("Element::Entry expected")
                         };
                     }
                 }
             } else {
-                self.add_error("Cannot finish a dictionary");
+                self.add_error(ParserErrorKind::UnterminatedDictionary, "Cannot finish a dictionary");
                 break;
             }
         }
@@ -252,121 +722,397 @@ impl<'a> Parser<'a> {
     }
 
     fn number(&mut self) -> Option<Value> {
-        let mut is_float = false;
-        let prefix = self.integer()?;
-        let decimal = if self.eat('.') {
-            is_float = true;
-            Some(self.integer())?
-        } else {
-            None
-        };
+        let start = self.pos;
+        match read_number(self.rest()) {
+            Ok((value, len)) => {
+                self.pos += len;
+                Some(value)
+            },
+            Err(error) => {
+                self.pos = start + error.pos;
+                self.add_error_at(start + error.lo, start + error.hi, error.kind, &error.message);
+                None
+            },
+        }
+    }
 
-        let input = match decimal {
-            Some(ref decimal) => prefix + "." + decimal,
-            None          => prefix
-        };
+    // dates start with `YYYY-`, times with `HH:`
+    fn is_date_or_time(&mut self) -> bool {
+        let rest = self.rest().as_bytes();
+        let is_digits = |n: usize| rest.len() > n && rest[..n].iter().all(u8::is_ascii_digit);
 
-        if is_float {
-            input.parse().ok().map(Value::Float)
-        } else {
-            input.parse().ok().map(Value::Integer)
-        }
+        (is_digits(4) && rest[4] == b'-') || (is_digits(2) && rest[2] == b':')
     }
 
-    fn integer(&mut self) -> Option<String> {
-        self.slice_while(|ch| match ch {
-            '0' ..= '9' => true,
-            _ => false
-        }).map(str::to_owned)
+    fn date_time(&mut self) -> Option<Value> {
+        let input = self.slice_while(|ch| matches!(ch, '0' ..= '9' | '-' | '+' | ':' | '.' | 'T' | 't' | 'Z' | 'z'))
+            .unwrap_or("");
+
+        let value = if input.contains('T') || input.contains('t') {
+            input.parse::<DateTime>().ok().map(Value::DateTime)
+        } else if input.contains(':') {
+            input.parse().ok().map(Value::Time)
+        } else {
+            input.parse().ok().map(Value::Date)
+        };
+
+        if value.is_none() {
+            self.add_error(ParserErrorKind::InvalidDateTime, "Cannot read a date or time, expected 'YYYY-MM-DD', 'HH:MM:SS' or 'YYYY-MM-DDTHH:MM:SS+HH:MM'");
+        }
+        value
     }
 
     fn boolean(&mut self, start: usize) -> Option<Value> {
         let rest = &self.input[start..];
+        if self.strict {
+            let word = rest.find(|c| !is_key_char(c)).map_or(rest, |end| &rest[..end]);
+            if word != "true" && word != "false" {
+                self.add_error_at(start, start + word.len(), ParserErrorKind::InvalidValue, "Cannot read a value, expected 'true' or 'false'");
+                return None;
+            }
+        }
 
         if rest.starts_with("true") {
-            for _ in 0..4 {
-                self.cur.next();
-            }
+            self.pos += 4;
             Some(Value::Boolean(true))
         } else if rest.starts_with("false") {
-            for _ in 0..5 {
-                self.cur.next();
-            }
+            self.pos += 5;
             Some(Value::Boolean(false))
         } else {
             None
         }
     }
 
-    fn finish_string(&mut self) -> Option<Value> {
-        self.cur.next();
-        self.slice_to_exc('"').map(|s| Value::String(s.to_string()))
+    fn null(&mut self) -> Option<Value> {
+        if self.rest().starts_with("null") {
+            self.pos += 4;
+            Some(Value::Null)
+        } else {
+            self.add_error(ParserErrorKind::InvalidValue, "Cannot read a value");
+            None
+        }
+    }
+
+    fn finish_string(&mut self) -> Option<Cow<'a, str>> {
+        if self.rest().starts_with("\"\"\"") { return self.finish_multi_line_string("\"\"\"") }
+        if !self.eat('"') { return None }
+
+        let lo = self.pos();
+        let hi = self.string_end("\"", true)?;
+        self.unescaped(lo, hi)
+    }
+
+    fn finish_raw_string(&mut self) -> Option<Cow<'a, str>> {
+        if self.rest().starts_with("'''") { return self.finish_multi_line_string("'''") }
+        if !self.eat('\'') { return None }
+
+        let lo = self.pos();
+        let hi = self.string_end("'", false)?;
+        Some(Cow::Borrowed(&self.input[lo..hi]))
+    }
+
+    // Multi-line strings are enclosed in `"""` (with escape sequences) or `'''` (raw).
+    // A newline directly after the opening delimiter is not part of the string. When the closing
+    // delimiter is on its own line, that line is dropped and its indentation is stripped from
+    // every line of the string.
+    fn finish_multi_line_string(&mut self, delimiter: &str) -> Option<Cow<'a, str>> {
+        self.pos += delimiter.len();
+
+        let mut lo = self.pos();
+        let hi = self.string_end(delimiter, delimiter == "\"\"\"")?;
+
+        let mut content = &self.input[lo..hi];
+        let opening_newline = content.starts_with('\n') || content.starts_with("\r\n");
+        if opening_newline {
+            let len = if content.starts_with('\n') { 1 } else { 2 };
+            content = &content[len..];
+            lo += len;
+        }
+
+        let is_blank = |s: &str| s.chars().all(|c| c == ' ' || c == '\t');
+        let (content, indent) = match content.rfind('\n') {
+            Some(nl) if is_blank(&content[nl + 1..]) => (&content[..nl], &content[nl + 1..]),
+            None if opening_newline && is_blank(content) => ("", content),
+            _ => (content, ""),
+        };
+
+        let strip = |line: &'a str| line.strip_prefix(indent).unwrap_or_else(|| line.trim_start_matches([' ', '\t']));
+
+        // borrowed when there is no indentation, carriage return or escape sequence to remove
+        let verbatim = !content.contains('\r')
+            && (delimiter == "'''" || !content.contains('\\'))
+            && content.split('\n').all(|line| strip(line).len() == line.len());
+        if verbatim { return Some(Cow::Borrowed(content)) }
+
+        let mut out = String::with_capacity(content.len());
+        for (i, raw_line) in content.split('\n').enumerate() {
+            let line = raw_line.trim_end_matches('\r');
+            let text = strip(line);
+
+            if i > 0 { out.push('\n') }
+            let text_lo = lo + (line.len() - text.len());
+            if delimiter == "'''" {
+                out.push_str(text);
+            } else if !self.unescape(text_lo, text_lo + text.len(), &mut out) {
+                return None;
+            }
+            lo += raw_line.len() + 1;
+        }
+
+        Some(Cow::Owned(out))
+    }
+
+    // consumes the string content and the closing `quote`, returning the end of the content
+    // when `escapes` is set, a backslash escapes the following character
+    // An unterminated string ends at the end of the input, unless the parser is strict: then
+    // it is an error and single-line strings also end at the end of their line.
+    fn string_end(&mut self, quote: &str, escapes: bool) -> Option<usize> {
+        let first = quote.as_bytes()[0];
+        let lo = self.pos() - quote.len();
+        let stop_at_newline = self.strict && quote.len() == 1;
+        let bytes = self.input.as_bytes();
+
+        loop {
+            let stop = bytes[self.pos..].iter()
+                .position(|&b| b == first || (b == b'\\' && escapes) || (b == b'\n' && stop_at_newline))
+                .map(|i| self.pos + i);
+            match stop {
+                Some(end) if bytes[end] == first => {
+                    self.pos = end + 1;
+                    if self.input[end..].starts_with(quote) {
+                        self.pos = end + quote.len();
+                        return Some(end);
+                    }
+                },
+                Some(end) if bytes[end] == b'\\' => {
+                    self.pos = end + 1;
+                    self.pos += self.peek().map_or(0, char::len_utf8);
+                },
+                Some(end) => {
+                    self.pos = end;
+                    break;
+                },
+                None => {
+                    self.pos = self.input.len();
+                    if self.strict { break }
+                    return Some(self.input.len());
+                },
+            }
+        }
+
+        let hi = self.rest().find(['\r', '\n']).map_or(self.input.len(), |i| self.pos() + i);
+        self.add_error_at(lo, hi, ParserErrorKind::UnterminatedString, &format!("Cannot finish a string, expected a closing {}", quote));
+        None
+    }
+
+    // `self.input[lo..hi]` with its escape sequences decoded, borrowed if there are none
+    fn unescaped(&mut self, lo: usize, hi: usize) -> Option<Cow<'a, str>> {
+        let raw = &self.input[lo..hi];
+        if !raw.contains('\\') { return Some(Cow::Borrowed(raw)) }
+
+        let mut out = String::with_capacity(raw.len());
+        if !self.unescape(lo, hi, &mut out) { return None }
+        Some(Cow::Owned(out))
+    }
+
+    // decodes the escape sequences of `self.input[lo..hi]` into `out`
+    fn unescape(&mut self, lo: usize, hi: usize, out: &mut String) -> bool {
+        match unescape(&self.input[lo..hi], out) {
+            Ok(()) => true,
+            Err((err_lo, err_hi, message)) => {
+                self.add_error_at(lo + err_lo, lo + err_hi, ParserErrorKind::InvalidEscape, message);
+                false
+            }
+        }
     }
 
     fn keyval_sep(&mut self) -> bool {
         self.ws();
-        if !self.expect('=') { return false }
+        if !self.eat('=') {
+            self.add_error(ParserErrorKind::MissingEquals, "Expected '='");
+            return false;
+        }
         self.ws();
         true
     }
 
-    fn expect(&mut self, ch: char) -> bool {
-        self.eat(ch)
-    }
-
-    fn row(&mut self) -> Option<Element> {
+    fn row<B: Build<'a>>(&mut self) -> Option<Parsed<'a, B>> {
         let mut row  = Vec::with_capacity(self.row_capacity);
+        let infer_cells = self.infer_cells && !self.is_header_row();
         self.eat('|');
 
         loop {
             self.ws();
-            if self.comment().is_some() { break } // this will eat and NOT return comments within tables
+            if self.comment::<B>().is_some() { break } // this will eat and NOT return comments within tables
             if self.newline() { break }
-            if self.cur.peek().is_none() { break }
+            if self.pos == self.input.len() { break }
 
-            row.push(Value::String(self.cell()));
+            let value = match self.quoted_cell() {
+                Some(cell) => B::string(cell),
+                None if infer_cells => {
+                    let cell = self.cell();
+                    infer_cell(&cell).map_or_else(|| B::string(cell), B::scalar)
+                },
+                None => B::string(self.cell()),
+            };
+            row.push(value);
         }
 
-        Some(Element::Row(row))
+        Some(Parsed::Row(row))
+    }
+
+    // whether the row at `pos` is followed by a `|---|` separator, which makes it the header
+    fn is_header_row(&self) -> bool {
+        let next = match self.rest().find('\n') {
+            Some(i) => &self.rest()[i + 1..],
+            None => return false,
+        };
+        let next = next.trim_start_matches(|c| c == ' ' || c == '\t');
+        if !next.starts_with('|') { return false }
+        let first = next[1..].split(|c| c == '|' || c == '\n').next().unwrap_or("").trim();
+        !first.is_empty() && first.chars().all(|c| c == '-')
     }
 
-    fn cell(&mut self) -> String {
+    // a cell up to the next `|` that is not escaped as `\|` or the end of the line, without
+    // trailing whitespace. Other backslashes are kept as they are.
+    fn cell(&mut self) -> Cow<'a, str> {
         self.ws();
-        self.slice_to_exc('|').map(str::trim_end).unwrap_or("").to_owned()
+        let rest = self.rest();
+        let mut end = rest.len();
+        let mut from = 0;
+        while let Some(i) = rest[from..].find(['|', '\n']).map(|i| from + i) {
+            if rest.as_bytes()[i] == b'\n' || !rest[..i].ends_with('\\') { end = i; break }
+            from = i + 1;
+        }
+        self.pos += if rest[end..].starts_with('|') { end + 1 } else { end };
+
+        let cell = rest[..end].trim_end();
+        if cell.contains("\\|") { Cow::Owned(cell.replace("\\|", "|")) } else { Cow::Borrowed(cell) }
     }
 
+    // a cell enclosed in `"` with the escape sequences of strings, followed by the end of the
+    // cell. `None` if the cell is not quoted, in which case nothing is consumed.
+    fn quoted_cell(&mut self) -> Option<Cow<'a, str>> {
+        let rest = self.rest();
+        if !rest.starts_with('"') { return None }
+
+        let mut chars = rest.char_indices().skip(1);
+        let mut end = None;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => { chars.next(); },
+                '"' => { end = Some(i); break },
+                '\n' | '\r' => break,
+                _ => (),
+            }
+        }
+        let end = end?;
+        let after = rest[end + 1..].trim_start_matches([' ', '\t']);
+        if !(after.is_empty() || after.starts_with(['|', '\n', '\r'])) { return None }
+
+        let raw = &rest[1..end];
+        let cell = if raw.contains('\\') {
+            let mut cell = String::with_capacity(raw.len());
+            unescape(raw, &mut cell).ok()?;
+            Cow::Owned(cell)
+        } else {
+            Cow::Borrowed(raw)
+        };
+
+        self.pos += rest.len() - after.len() + if after.starts_with('|') { 1 } else { 0 };
+        Some(cell)
+    }
+
+    /// Reads all sections, `#include` directives are reported as errors as there is no
+    /// `Resolver` to load the included files with. Returns `None` if there are errors,
+    /// unless the parser was created `with_recovery`.
     pub fn read(&mut self) -> Option<BTreeMap<String, Section>> {
-        let mut map = BTreeMap::new();
+        self.read_resolving::<Owned>(None)
+    }
+
+    /// Like `read`, only the sections borrow their keys and strings from the input where they
+    /// are unchanged by parsing, see `IonRef`
+    pub fn read_borrowed(&mut self) -> Option<BTreeMap<Cow<'a, str>, SectionRef<'a>>> {
+        self.read_resolving::<Borrowed<'a>>(None)
+    }
+
+    /// Reads all sections, loading the files named by `#include` directives through `resolver`.
+    /// The included sections are read as if the directive was replaced by the file's content,
+    /// errors in an included file carry its name in `ParserError::file`.
+    pub fn read_with_resolver(&mut self, resolver: &dyn Resolver) -> Option<BTreeMap<String, Section>> {
+        self.read_resolving::<Owned>(Some(resolver))
+    }
+
+    fn read_resolving<B: Build<'a>>(&mut self, resolver: Option<&dyn Resolver>) -> Option<BTreeMap<B::Key, B::Section>> {
+        let duplicates = self.duplicate_sections.unwrap_or(DuplicateSections::KeepLast);
+        let mut sections = Sections::new(self.section_capacity, duplicates, self.duplicate_keys);
+        let mut including = self.file.iter().cloned().collect();
+        self.read_into::<B>(&mut sections, resolver, &mut including);
+        let map = sections.finish(self.accepted_sections.is_none());
 
-        let mut section = Section::with_capacity(self.section_capacity);
-        let mut name    = None;
+        if !self.errors.is_empty() && !self.recover {
+            None
+        } else {
+            Some(map)
+        }
+    }
 
-        while let Some(el) = self.next() {
+    // `including` holds the names of the files whose includes are being resolved
+    fn read_into<B: Build<'a>>(&mut self, sections: &mut Sections<B>, resolver: Option<&dyn Resolver>, including: &mut Vec<String>) {
+        while let Some(el) = self.next_element::<B>() {
             match el {
-                Element::Section(n) => {
-                    if let Some(name) = name {
-                        map.insert(name, section);
+                Parsed::Include(path) => self.read_include(&path, sections, resolver, including),
+                Parsed::Section(name) => {
+                    let (lo, hi) = self.span;
+                    let location = self.location(lo);
+                    if let Some(desc) = sections.start(name, location) {
+                        self.add_error_at(lo, hi, ParserErrorKind::DuplicateSection, &desc);
                     }
-                    name = Some(n);
-                    section = Section::with_capacity(self.section_capacity);
                 },
-                Element::Row(row) => section.rows.push(row),
-                Element::Entry(key, value) => { section.dictionary.insert(key, value); },
-                _ => continue
-            };
+                Parsed::Entry(key, value) => {
+                    let (lo, hi) = self.span;
+                    let location = if sections.tracks_keys() { self.location(lo) } else { String::new() };
+                    if let Some(first) = sections.entry(key.borrow(), location) {
+                        self.duplicate_key(lo, hi, key.borrow(), &first);
+                    }
+                    sections.push(Parsed::Entry(key, value));
+                },
+                el => sections.push(el),
+            }
         }
+    }
 
-        match name {
-            Some(name) => map.insert(name, section),
-            None if self.accepted_sections.is_none() => map.insert("root".to_string(), section),
-            _ => None,
+    fn read_include<B: Output>(&mut self, path: &str, sections: &mut Sections<B>, resolver: Option<&dyn Resolver>, including: &mut Vec<String>) {
+        let (lo, hi) = self.span;
+        let resolver = match resolver {
+            Some(resolver) => resolver,
+            None => {
+                self.add_error_at(lo, hi, ParserErrorKind::Include, &format!("Cannot include '{}' without a resolver", path));
+                return;
+            }
         };
 
-        if self.errors.len() > 0 {
-            None
-        } else {
-            Some(map)
+        let (file, content) = match resolver.resolve(path, self.file.as_deref()) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                self.add_error_at(lo, hi, ParserErrorKind::Include, &format!("Cannot include '{}': {}", path, e));
+                return;
+            }
+        };
+        if including.contains(&file) {
+            self.add_error_at(lo, hi, ParserErrorKind::CircularInclude, &format!("Cannot include '{}', it is already being included", file));
+            return;
         }
+
+        let mut parser = self.included(&content, file.clone());
+        including.push(file);
+        let read = B::read_included(&mut parser, sections, resolver, including);
+        including.pop();
+        if !read {
+            self.add_error_at(lo, hi, ParserErrorKind::Include, &format!("Cannot include '{}' into sections that borrow from the input", path));
+        }
+        self.errors.append(&mut parser.errors);
+        self.warnings.append(&mut parser.warnings);
     }
 
     fn is_section_accepted(&mut self, name: &str) -> Option<bool> {
@@ -378,6 +1124,7 @@ impl<'a> Parser<'a> {
             return None
         }
         match sections.iter().position(|s| *s == name) {
+            Some(_) if self.duplicate_sections.is_some() => Some(true),
             Some(idx) => {
                 sections.swap_remove(idx);
                 Some(true)
@@ -387,110 +1134,460 @@ impl<'a> Parser<'a> {
     }
 
     // returns slice from the next character to `ch`, inclusive
-    // after this function, self.cur.next() returns the next character after `ch`
+    // after this function, self.peek() returns the next character after `ch`
     // None is only returned if the input is empty
     // Examples:
-    // Parser::new("foObar").slice_to_inc('b') == Some("foOb"), self.cur.next() == (4, 'a')
-    // Parser::new("foObar").slice_to_inc('f') == Some("f"),    self.cur.next() == (1, 'o')
-    fn slice_to_inc(&mut self, ch: char) -> Option<&str> {
-        self.cur
-            .next()
-            .and_then(|(start, c)|
-                if c == ch {
-                    Some(&self.input[start..=start])
-                }
-                else {
-                    Some(
-                        self.cur
-                            .find(|(_, c)| *c == ch)
-                            .map_or(
-                                &self.input[start..],
-                                |(end, _)| &self.input[start..=end]
-                            )
-                    )
-                }
-            )
-    }
-
-    // returns slice from the next character to `ch`
-    // the result is exclusive (does not contain `ch`), but the functions consumes `ch`
-    // None is returned when the input is empty or when `ch` is the next character
-    // Examples:
-    // Parser::new("foObar").slice_to_exc('b') == Some("foO"), self.cur.next() == (4, 'a')
-    // Parser::new("foObar").slice_to_exc('f') == None,        self.cur.next() == (1, 'o')
-    fn slice_to_exc(&mut self, ch: char) -> Option<&str> {
-        self.cur
-            .next()
-            .and_then(|(start, c)|
-                if c == ch {
-                    Some("")
-                } else {
-                    Some(
-                        self.cur
-                            .find(|(_, c)| *c == ch)
-                            .map_or(
-                                &self.input[start..],
-                                |(end, _)| &self.input[start..end]
-                            )
-                    )
-                }
-            )
+    // Parser::new("foObar").slice_to_inc('b') == Some("foOb"), self.peek() == Some('a')
+    // Parser::new("foObar").slice_to_inc('f') == Some("f"),    self.peek() == Some('o')
+    fn slice_to_inc(&mut self, ch: char) -> Option<&'a str> {
+        let rest = self.rest();
+        if rest.is_empty() { return None }
+
+        let end = rest.find(ch).map_or(rest.len(), |i| i + ch.len_utf8());
+        self.pos += end;
+        Some(&rest[..end])
     }
 
     // returns slice from the next character to the last consecutive character matching the predicate
     // the result is exclusive (does not contain `ch`) and does not consume `ch`
     // None is returned when the input is empty or when `ch` is the next character
     // Examples:
-    // Parser::new("foObar").slice_while(|c| c != 'b') == Some("foO"), self.cur.next() == (3, 'b')
-    // Parser::new("foObar").slice_while(|c| c != 'f') == None,        self.cur.next() == (0, 'f')
-    fn slice_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<&str> {
-        self.cur
-            .peek()
-            .cloned()
-            .and_then(|(start, c)|
-                if !predicate(c) {
-                    None
-                }
-                else {
-                    self.cur.next();
+    // Parser::new("foObar").slice_while(|c| c != 'b') == Some("foO"), self.peek() == Some('b')
+    // Parser::new("foObar").slice_while(|c| c != 'f') == None,        self.peek() == Some('f')
+    fn slice_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<&'a str> {
+        let rest = self.rest();
+        let end = rest.char_indices().find(|&(_, c)| !predicate(c)).map_or(rest.len(), |(i, _)| i);
+        if end == 0 { return None }
+
+        self.pos += end;
+        Some(&rest[..end])
+    }
 
-                    while let Some(&(end, c)) = self.cur.peek() {
-                        if !predicate(c) {
-                            return Some(&self.input[start..end]);
-                        }
+    fn add_error(&mut self, kind: ParserErrorKind, message: &str) {
+        let lo = self.pos;
+        let hi = lo + self.peek().map_or(0, char::len_utf8);
+
+        self.add_error_at(lo, hi, kind, message);
+    }
+
+    fn add_error_at(&mut self, lo: usize, hi: usize, kind: ParserErrorKind, message: &str) {
+        let error = self.error_at(lo, hi, kind, message);
+        self.errors.push(error);
+    }
+
+    fn error_at(&self, lo: usize, hi: usize, kind: ParserErrorKind, message: &str) -> ParserError {
+        let (line, col) = line_col(self.input, lo);
+        ParserError {
+            lo, hi,
+            line, col,
+            kind,
+            desc: message.to_owned(),
+            file: self.file.clone(),
+        }
+    }
+
+    // `line:col` of the byte `pos`, preceded by the file name if there is one, like the
+    // position of a `ParserError`. Counts the lines from the last position located, which is
+    // close to `pos`.
+    fn location(&mut self, pos: usize) -> String {
+        let (from, mut line, mut line_start) = self.located;
+        if from <= pos {
+            for (i, _) in self.input[from..pos].match_indices('\n') {
+                line += 1;
+                line_start = from + i + 1;
+            }
+        } else {
+            line -= self.input[pos..from].matches('\n').count();
+            line_start = self.input[..pos].rfind('\n').map_or(0, |i| i + 1);
+        }
+        self.located = (pos, line, line_start);
+
+        let col = self.input[line_start..pos].chars().count() + 1;
+        match self.file {
+            Some(ref file) => format!("{}:{}:{}", file, line, col),
+            None => format!("{}:{}", line, col),
+        }
+    }
+
+    // `first` is the location of the first definition of `key`
+    fn duplicate_key(&mut self, lo: usize, hi: usize, key: &str, first: &str) {
+        let error = self.error_at(lo, hi, ParserErrorKind::DuplicateKey, &format!("Duplicate key '{}', first defined at {}", key, first));
+        match self.duplicate_keys {
+            DuplicateKeys::Ignore => (),
+            DuplicateKeys::Warn => self.warnings.push(error),
+            DuplicateKeys::Error => self.errors.push(error),
+        }
+    }
+}
+
+// collects elements into named sections, shared by the parsers of the included files
+struct Sections<B: Output> {
+    map: BTreeMap<B::Key, B::Section>,
+    name: Option<B::Key>,
+    section: B::Section,
+    capacity: usize,
+    duplicates: DuplicateSections,
+    // where each section was first defined
+    origins: BTreeMap<String, String>,
+    // where each key was defined by section name, if duplicate keys are looked for. Only merged
+    // sections keep the keys of their earlier definitions.
+    keys: Option<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl<B: Output> Sections<B> {
+    fn new(capacity: usize, duplicates: DuplicateSections, duplicate_keys: DuplicateKeys) -> Sections<B> {
+        Sections {
+            map: BTreeMap::new(),
+            name: None,
+            section: B::section(capacity),
+            capacity,
+            duplicates,
+            origins: BTreeMap::new(),
+            keys: if duplicate_keys == DuplicateKeys::Ignore { None } else { Some(BTreeMap::new()) },
+        }
+    }
+
+    // starts the section `name` defined at `location`, returns an error description if it was
+    // already defined and duplicates are errors
+    fn start(&mut self, name: B::Key, location: String) -> Option<String> {
+        self.close();
+        if let Some(ref mut keys) = self.keys {
+            if self.duplicates != DuplicateSections::Merge {
+                keys.clear();
+            }
+        }
+
+        let error = match self.origins.get(name.borrow()) {
+            Some(first) if self.duplicates == DuplicateSections::Error => {
+                Some(format!("Duplicate section '{}', first defined at {}", name.borrow(), first))
+            },
+            Some(_) => None,
+            None => {
+                self.origins.insert(name.borrow().to_owned(), location);
+                None
+            }
+        };
+        self.name = Some(name);
+        error
+    }
+
+    // whether `entry` needs the locations of the keys
+    fn tracks_keys(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    // records the key of an entry at `location`, returns where it was first defined in the
+    // current section if it is a duplicate
+    fn entry(&mut self, key: &str, location: String) -> Option<String> {
+        let name = self.name.as_ref().map_or("", |name| name.borrow());
+        let sections = self.keys.as_mut()?;
+        if !sections.contains_key(name) {
+            sections.insert(name.to_owned(), BTreeMap::new());
+        }
+        let keys = sections.get_mut(name)?;
+        if let Some(first) = keys.get(key) {
+            return Some(first.clone());
+        }
+        keys.insert(key.to_owned(), location);
+        None
+    }
+
+    // sections are started with `start`
+    fn push(&mut self, el: Parsed<B>) {
+        match el {
+            Parsed::Row(row) => B::push_row(&mut self.section, row),
+            Parsed::Entry(key, value) => B::insert(&mut self.section, key, value),
+            Parsed::Section(_) | Parsed::Comment(_) | Parsed::Include(_) => (),
+        }
+    }
+
+    fn close(&mut self) {
+        let section = mem::replace(&mut self.section, B::section(self.capacity));
+        let name = match self.name.take() {
+            Some(name) => name,
+            None => return,
+        };
+
+        match self.map.entry(name) {
+            btree_map::Entry::Vacant(entry) => { entry.insert(section); },
+            btree_map::Entry::Occupied(mut entry) => match self.duplicates {
+                DuplicateSections::Error | DuplicateSections::KeepFirst => (),
+                DuplicateSections::KeepLast => { entry.insert(section); },
+                DuplicateSections::Merge => B::merge(entry.get_mut(), section),
+            },
+        }
+    }
+
+    // `with_root` keeps the elements before the first section as the "root" section
+    fn finish(mut self, with_root: bool) -> BTreeMap<B::Key, B::Section> {
+        if self.name.is_none() && with_root {
+            self.name = Some(B::root());
+        }
+        self.close();
+        self.map
+    }
+}
+
+// reads `cell` as an integer, float, boolean or `null` value where it is one, with the grammar
+// of `Parser::number`, an empty cell is null as well. `None` for anything else, which stays a string.
+pub fn infer_cell(cell: &str) -> Option<Value> {
+    match cell {
+        "" | "null" => Some(Value::Null),
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => match read_number(cell) {
+            Ok((value, len)) if len == cell.len() => Some(value),
+            _ => None,
+        },
+    }
+}
+
+// why `read_number` could not read a number: the byte range to report, and where it stopped
+struct NumberError {
+    lo: usize,
+    hi: usize,
+    pos: usize,
+    kind: ParserErrorKind,
+    message: String,
+}
+
+// reads the number at the start of `s`: a sign, then `inf`, `nan`, digits after a `0x`, `0o` or
+// `0b` radix, or decimal digits with a fraction and an exponent. Digits may be separated by single
+// '_'. Returns the value and its length in bytes.
+fn read_number(s: &str) -> Result<(Value, usize), NumberError> {
+    let mut pos = 0;
+    let negative = s.starts_with('-');
+    if negative || s.starts_with('+') { pos += 1 }
+
+    let special = match s.get(pos..pos + 3) {
+        Some("inf") => Some(f64::INFINITY),
+        Some("nan") => Some(f64::NAN),
+        _ => None,
+    };
+    if let Some(v) = special {
+        return Ok((Value::Float(if negative { -v } else { v }), pos + 3));
+    }
+
+    let radix = match s.get(pos..pos + 2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+
+    let mut input = String::new();
+    if negative { input.push('-') }
+
+    if radix != 10 {
+        pos = digits(s, pos + 2, radix, "", &mut input)?;
+        return integer(&input, radix, pos);
+    }
+
+    pos = digits(s, pos, 10, "", &mut input)?;
+    let mut is_float = false;
+
+    if s[pos..].starts_with('.') {
+        is_float = true;
+        input.push('.');
+        pos = digits(s, pos + 1, 10, " after '.'", &mut input)?;
+    }
+
+    if s[pos..].starts_with(|c| c == 'e' || c == 'E') {
+        is_float = true;
+        input.push('e');
+        pos += 1;
+        if s[pos..].starts_with('-') { input.push('-') }
+        if s[pos..].starts_with(|c| c == '-' || c == '+') { pos += 1 }
+        pos = digits(s, pos, 10, " in the exponent", &mut input)?;
+    }
+
+    if is_float {
+        let value = input.parse().map_err(|_| number_error(0, pos, pos, ParserErrorKind::InvalidNumber, "Cannot read a number".to_owned()))?;
+        Ok((Value::Float(value), pos))
+    } else {
+        integer(&input, 10, pos)
+    }
+}
 
-                        self.cur.next();
-                    }
+fn integer(input: &str, radix: u32, len: usize) -> Result<(Value, usize), NumberError> {
+    match i64::from_str_radix(input, radix) {
+        Ok(v) => Ok((Value::Integer(v), len)),
+        Err(_) => Err(number_error(0, len, len, ParserErrorKind::IntegerOutOfRange, "Integer is out of range for a 64-bit signed integer".to_owned())),
+    }
+}
 
-                    Some(&self.input[start..])
-                }
-            )
+// reads the digits of the given radix at `start` of `s`, which may be separated by single '_',
+// pushes them without the separators to `input` and returns the position after them
+fn digits(s: &str, start: usize, radix: u32, location: &str, input: &mut String) -> Result<usize, NumberError> {
+    let rest = &s[start..];
+    let len = rest.char_indices().find(|&(_, c)| !c.is_digit(radix) && c != '_').map_or(rest.len(), |(i, _)| i);
+    let raw = &rest[..len];
+    let end = start + len;
+
+    if !raw.starts_with(|c: char| c.is_digit(radix)) {
+        let hi = end + s[end..].chars().next().map_or(0, char::len_utf8);
+        return Err(number_error(end, hi, end, ParserErrorKind::InvalidNumber, format!("Cannot read a number, a digit is expected{}", location)));
     }
+    if raw.ends_with('_') || raw.contains("__") {
+        return Err(number_error(start, end, end, ParserErrorKind::InvalidNumber, "Cannot read a number, '_' is only allowed between digits".to_owned()));
+    }
+
+    input.extend(raw.chars().filter(|&c| c != '_'));
+    Ok(end)
+}
 
-    fn add_error(&mut self, message: &str) {
-        let mut it = self.cur.clone();
-        let lo = it.next().map(|p| p.0).unwrap_or(self.input.len());
-        let hi = it.next().map(|p| p.0).unwrap_or(self.input.len());
+fn number_error(lo: usize, hi: usize, pos: usize, kind: ParserErrorKind, message: String) -> NumberError {
+    NumberError { lo, hi, pos, kind, message }
+}
 
-        self.errors.push(ParserError{
-            lo: lo, hi: hi,
-            desc: message.to_owned()
-        });
+// moves the positions of `errors` found in a part of a larger input, which starts at the byte
+// `offset` after `lines` lines
+pub fn offset_errors(errors: &mut [ParserError], offset: usize, lines: usize) {
+    for error in errors {
+        error.lo += offset;
+        error.hi += offset;
+        error.line += lines;
     }
 }
 
+// the 1-based line and column, in characters, of the byte `pos` of `input`
+fn line_col(input: &str, pos: usize) -> (usize, usize) {
+    let before = &input[..pos];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+
 fn is_digit(c: char) -> bool {
     match c { '0' ..= '9' => true, _ => false }
 }
 
+/// Characters allowed in keys that are not quoted
+pub fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+// replaces the escape sequences in `raw`, on failure returns the range of the invalid sequence
+fn unescape(raw: &str, out: &mut String) -> Result<(), (usize, usize, &'static str)> {
+    let mut start = 0;
+
+    while let Some(i) = raw[start..].find('\\').map(|i| start + i) {
+        out.push_str(&raw[start..i]);
+        let rest = &raw[i + 1..];
+        let (ch, len) = match rest.chars().next() {
+            Some('"')  => ('"', 1),
+            Some('\\') => ('\\', 1),
+            Some('n')  => ('\n', 1),
+            Some('r')  => ('\r', 1),
+            Some('t')  => ('\t', 1),
+            Some('u')  => match unicode_escape(&rest[1..]) {
+                Some((ch, len)) => (ch, len + 1),
+                None => return Err((i, i + 2, "Invalid unicode escape sequence, expected '\\u{XXXX}'")),
+            },
+            Some(c) => return Err((i, i + 1 + c.len_utf8(), "Invalid escape sequence")),
+            None    => return Err((i, i + 1, "Invalid escape sequence")),
+        };
+        out.push(ch);
+        start = i + 1 + len;
+    }
+
+    out.push_str(&raw[start..]);
+    Ok(())
+}
+
+// reads `{XXXX}` with 1 to 6 hex digits, returning the character and the length of the sequence
+fn unicode_escape(s: &str) -> Option<(char, usize)> {
+    if !s.starts_with('{') { return None }
+    let end = s.find('}')?;
+    let hex = &s[1..end];
+    if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+        .and_then(::std::char::from_u32)
+        .map(|ch| (ch, end + 1))
+}
+
 #[derive(Clone, Debug)]
 pub struct ParserError {
     /// The low byte at which this error is pointing at.
     pub lo: usize,
     /// One byte beyond the last character at which this error is pointing at.
     pub hi: usize,
+    /// The line of `lo`, starting at 1.
+    pub line: usize,
+    /// The column of `lo` in characters, starting at 1.
+    pub col: usize,
+    /// What kind of error this is.
+    pub kind: ParserErrorKind,
     /// A human-readable description explaining what the error is.
     pub desc: String,
+    /// The file this error was found in, `None` unless the input was named with
+    /// `Parser::with_file_name` or the error is in an included file.
+    pub file: Option<String>,
+}
+
+/// The kinds of `ParserError`s, each with a code that stays the same across releases
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParserErrorKind {
+    /// No value could be read after a `=`, in an array or in a dictionary
+    InvalidValue,
+    /// A key is neither a bare nor a quoted key
+    InvalidKey,
+    /// A key is not followed by `=`
+    MissingEquals,
+    /// An array is missing its closing `]`
+    UnterminatedArray,
+    /// A dictionary is missing its closing `}`
+    UnterminatedDictionary,
+    InvalidNumber,
+    /// An integer does not fit into an `i64`
+    IntegerOutOfRange,
+    /// An unknown escape sequence in a string or quoted key
+    InvalidEscape,
+    InvalidDateTime,
+    /// A `${...}` reference is malformed
+    InvalidReference,
+    /// An included file could not be loaded
+    Include,
+    /// A file includes itself, directly or through other files
+    CircularInclude,
+    /// A section is defined again, see `DuplicateSections::Error`
+    DuplicateSection,
+    /// A key is defined again, see `DuplicateKeys`
+    DuplicateKey,
+    /// The input of a `SectionReader` could not be read
+    Io,
+    /// A string is missing its closing quote, only reported by a strict parser
+    UnterminatedString,
+    /// Something follows a complete entry on its line, only reported by a strict parser
+    TrailingCharacters,
+}
+
+impl ParserErrorKind {
+    /// The stable code of this kind, like `"E0004"` for `UnterminatedArray`
+    pub fn code(self) -> &'static str {
+        match self {
+            ParserErrorKind::InvalidValue => "E0001",
+            ParserErrorKind::InvalidKey => "E0002",
+            ParserErrorKind::MissingEquals => "E0003",
+            ParserErrorKind::UnterminatedArray => "E0004",
+            ParserErrorKind::UnterminatedDictionary => "E0005",
+            ParserErrorKind::InvalidNumber => "E0006",
+            ParserErrorKind::IntegerOutOfRange => "E0007",
+            ParserErrorKind::InvalidEscape => "E0008",
+            ParserErrorKind::InvalidDateTime => "E0009",
+            ParserErrorKind::InvalidReference => "E0010",
+            ParserErrorKind::Include => "E0011",
+            ParserErrorKind::CircularInclude => "E0012",
+            ParserErrorKind::DuplicateSection => "E0013",
+            ParserErrorKind::DuplicateKey => "E0014",
+            ParserErrorKind::Io => "E0015",
+            ParserErrorKind::UnterminatedString => "E0016",
+            ParserErrorKind::TrailingCharacters => "E0017",
+        }
+    }
 }
 
 impl error::Error for ParserError {
@@ -499,106 +1596,470 @@ impl error::Error for ParserError {
     }
 }
 
+/// `file:line:col: description [code]`, without the `file:` if there is no file name
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+        if let Some(ref file) = self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}:{}: {} [{}]", self.line, self.col, self.desc, self.kind.code())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Element::{self, Row, Entry, Comment};
-    use {Dictionary, Parser, Value, Section};
+    use super::{infer_cell, Owned};
+    use {Date, DateTime, Dictionary, DuplicateKeys, DuplicateSections, Parser, ParserErrorKind, Resolver, Value, ValueRef, Section, Time};
+    use ion::InferredCell;
     use std::collections::BTreeMap;
+    use std::io;
 
     #[test]
     fn finish_string() {
         let mut p = Parser::new("\"foObar\"");
-        assert_eq!(Some("foObar"), p.finish_string().unwrap().as_str());
+        assert_eq!(Some("foObar"), p.finish_string().as_deref());
 
         let mut p = Parser::new("\"foObar");
-        assert_eq!(Some("foObar"), p.finish_string().unwrap().as_str());
+        assert_eq!(Some("foObar"), p.finish_string().as_deref());
 
         let mut p = Parser::new("\"\"");
-        assert_eq!(Some(""), p.finish_string().unwrap().as_str());
+        assert_eq!(Some(""), p.finish_string().as_deref());
 
         let mut p = Parser::new("");
         assert_eq!(None, p.finish_string());
     }
 
+    #[test]
+    fn finish_string_with_escapes() {
+        let mut p = Parser::new(r#""say \"hi\"""#);
+        assert_eq!(Some("say \"hi\""), p.finish_string().as_deref());
+
+        let mut p = Parser::new(r#""a\\b\nc\td\re""#);
+        assert_eq!(Some("a\\b\nc\td\re"), p.finish_string().as_deref());
+
+        let mut p = Parser::new(r#""caf\u{e9} \u{1F600}""#);
+        assert_eq!(Some("café \u{1F600}"), p.finish_string().as_deref());
+    }
+
+    #[test]
+    fn finish_string_with_invalid_escapes() {
+        let mut p = Parser::new(r#""a\qb""#);
+        assert_eq!(None, p.finish_string());
+        assert_eq!("Invalid escape sequence", p.errors[0].desc);
+        assert_eq!((2, 4), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new(r#""\u{110000}""#);
+        assert_eq!(None, p.finish_string());
+        assert_eq!("Invalid unicode escape sequence, expected '\\u{XXXX}'", p.errors[0].desc);
+
+        let mut p = Parser::new(r#""\u00e9""#);
+        assert_eq!(None, p.finish_string());
+    }
+
+    #[test]
+    fn finish_raw_string() {
+        let mut p = Parser::new(r"'C:\temp\n'");
+        assert_eq!(Some(r"C:\temp\n"), p.value().unwrap().as_str());
+
+        let mut p = Parser::new("''");
+        assert_eq!(Some(""), p.value().unwrap().as_str());
+    }
+
+    #[test]
+    fn finish_multi_line_string() {
+        let raw = "\"\"\"\n    first \\\"line\\\"\n      second\n\n    third\n    \"\"\"";
+        let mut p = Parser::new(raw);
+        assert_eq!(Some("first \"line\"\n  second\n\nthird"), p.value().unwrap().as_str());
+
+        let mut p = Parser::new(r#""""single line""""#);
+        assert_eq!(Some("single line"), p.value().unwrap().as_str());
+
+        let mut p = Parser::new("\"\"\"\nkeeps\n  trailing\"\"\"");
+        assert_eq!(Some("keeps\n  trailing"), p.value().unwrap().as_str());
+
+        let mut p = Parser::new("\"\"\"\r\n  windows\r\n  lines\r\n  \"\"\"");
+        assert_eq!(Some("windows\nlines"), p.value().unwrap().as_str());
+
+        let mut p = Parser::new("\"\"\"\n  a\n  \\q\n  \"\"\"");
+        assert_eq!(None, p.value());
+        assert_eq!((10, 12), (p.errors[0].lo, p.errors[0].hi));
+    }
+
+    #[test]
+    fn finish_multi_line_raw_string() {
+        let raw = "'''\n    raw \\n \"\"\" text\n      indented\n    '''";
+        let mut p = Parser::new(raw);
+        assert_eq!(Some("raw \\n \"\"\" text\n  indented"), p.value().unwrap().as_str());
+    }
+
+    #[test]
+    fn multi_line_strings_borrow_without_changes() {
+        use std::borrow::Cow;
+
+        for &input in &["\"\"\"\nas is\n  \"\"\"", "'''raw \\n\n'''", "\"\"\"\"\"\""] {
+            let mut p = Parser::new(input);
+            match p.finish_multi_line_string(&input[..3]) {
+                Some(Cow::Borrowed(_)) => (),
+                other => panic!("{:?} is not borrowed: {:?}", input, other),
+            }
+        }
+
+        for &input in &["\"\"\"\n  a\n  \"\"\"", "\"\"\"\\t\"\"\"", "'''a\r\nb'''"] {
+            let mut p = Parser::new(input);
+            match p.finish_multi_line_string(&input[..3]) {
+                Some(Cow::Owned(_)) => (),
+                other => panic!("{:?} is not owned: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn multi_line_strings_in_filtered_sections() {
+        for quote in &["\"\"\"", "\'\'\'"] {
+            let raw = format!("[A]\ntext = {q}\n[B]\nfake = 1\n{q}\n[B]\nreal = 2\n", q = quote);
+
+            let mut p = Parser::new_filtered(&raw, vec!["B"]);
+            let filtered = p.read().expect("Read failed");
+            assert!(p.errors.is_empty());
+            assert_eq!(Some(&Value::Integer(2)), filtered["B"].get("real"));
+            assert_eq!(None, filtered["B"].get("fake"));
+
+            let all = Parser::new(&raw).read().unwrap();
+            assert_eq!(all.get("B"), filtered.get("B"));
+        }
+    }
+
+    #[test]
+    fn multi_line_string_round_trip() {
+        for s in &["a\nb", "a\n", "\na", "  indented\n\"\"\"quotes\"\"\"\n\ttab\r\n"] {
+            let v = Value::String(s.to_string());
+            let mut section = Section::new();
+            section.dictionary.insert("key".to_owned(), v.clone());
+
+            let raw = section.to_string();
+            assert!(raw.starts_with("key = \"\"\"\n"));
+
+            let mut p = Parser::new(&raw);
+            assert_eq!(Some(Entry("key".to_owned(), v)), p.next());
+        }
+    }
+
+    #[test]
+    fn escaped_string_round_trip() {
+        let s = Value::String("quote \" backslash \\ tab \t bell \u{7}".to_owned());
+        let mut section = Section::new();
+        section.dictionary.insert("key".to_owned(), s.clone());
+        section.dictionary.insert("ary".to_owned(), Value::Array(vec![s.clone()]));
+
+        let raw = section.to_string();
+        assert!(raw.contains(r#"key = "quote \" backslash \\ tab \t bell \u{7}""#));
+
+        let mut p = Parser::new(&raw);
+        assert_eq!(Some(Entry("ary".to_owned(), Value::Array(vec![s.clone()]))), p.next());
+        assert_eq!(Some(Entry("key".to_owned(), s)), p.next());
+    }
+
+    #[test]
+    fn escaped_and_quoted_cells() {
+        let mut p = Parser::new(r#"| a\|b | "x | y" | " # not a comment " | C:\temp\ | "a" b | "\q" |"#);
+        assert_eq!(Some(Row(vec![Value::new_string("a|b"), Value::new_string("x | y"), Value::new_string(" # not a comment "),
+                                 Value::new_string("C:\\temp\\"), Value::new_string("\"a\" b"), Value::new_string("\"\\q\"")])), p.next());
+
+        let mut p = Parser::new("| \"1\" | 1 | \"\" |").with_cell_type_inference();
+        assert_eq!(Some(Row(vec![Value::new_string("1"), Value::Integer(1), Value::new_string("")])), p.next());
+
+        let mut p = Parser::new("|a\\|b\\|\r\n|c|");
+        assert_eq!(Some(Row(vec![Value::new_string("a|b|")])), p.next());
+        assert_eq!(Some(Row(vec![Value::new_string("c")])), p.next());
+    }
+
+    #[quickcheck]
+    fn cell_round_trip(cells: Vec<String>) -> bool {
+        let row: Vec<Value> = cells.into_iter().map(Value::String).collect();
+        let mut section = Section::new();
+        section.rows.push(row.clone());
+
+        let raw = section.to_string();
+        let inferred: String = row.iter().map(|cell| format!("| {} ", InferredCell(cell))).collect::<String>() + "|";
+        let read = |mut p: Parser| match p.next() {
+            Some(Row(parsed)) => parsed == row,
+            None => row.is_empty(),
+            _ => false,
+        };
+        read(Parser::new(&raw)) && read(Parser::new(&inferred).with_cell_type_inference())
+    }
+
+    #[test]
+    fn cell_display() {
+        let row = vec![
+            Value::new_string("C:\\temp\\"), Value::new_string("1"), Value::new_string("true"), Value::new_string(""),
+            Value::new_string("-0x1F"), Value::Integer(1), Value::Array(vec![Value::new_string("a|b"), Value::Integer(2)]),
+        ];
+        let mut section = Section::new();
+        section.rows.push(row.clone());
+
+        let raw = section.to_string();
+        assert_eq!("| C:\\temp\\ | 1 | true |  | -0x1F | 1 | [ \"a\\|b\", 2 ] |\n", raw);
+        let mut expected = row.clone();
+        expected[6] = Value::new_string("[ \"a|b\", 2 ]");
+        let mut strings = expected.clone();
+        strings[5] = Value::new_string("1");
+        assert_eq!(Some(Row(strings)), Parser::new(&raw).next());
+
+        let inferred: String = row.iter().map(|cell| format!("| {} ", InferredCell(cell))).collect::<String>() + "|";
+        assert_eq!("| C:\\temp\\ | \"1\" | \"true\" | \"\" | \"-0x1F\" | 1 | [ \"a\\|b\", 2 ] |", inferred);
+        assert_eq!(Some(Row(expected)), Parser::new(&inferred).with_cell_type_inference().next());
+    }
+
     #[test]
     fn finish_array() {
         let mut p = Parser::new("[\"a\"");
-        assert_eq!(None, p.finish_array());
+        assert_eq!(None, p.finish_array::<Owned>());
 
         let mut p = Parser::new("[");
-        assert_eq!(None, p.finish_array());
+        assert_eq!(None, p.finish_array::<Owned>());
 
         let mut p = Parser::new("[]");
-        assert_eq!(Some(Value::Array(vec![])), p.finish_array());
+        assert_eq!(Some(Value::Array(vec![])), p.finish_array::<Owned>());
 
         let mut p = Parser::new("[\"a\"]");
-        assert_eq!(Some(Value::new_string_array("a")), p.finish_array());
+        assert_eq!(Some(Value::new_string_array("a")), p.finish_array::<Owned>());
     }
 
     #[test]
     fn finish_dictionary() {
         let mut p = Parser::new("{");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{ foo");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{ foo = ");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{ foo = \"bar\"");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{ foo = [\"bar\"");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{ foo = [\"bar\"]");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(None, p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{}");
-        assert_eq!(Some(Value::Dictionary(Dictionary::new())), p.finish_dictionary());
+        assert_eq!(Some(Value::Dictionary(Dictionary::new())), p.finish_dictionary::<Owned>());
 
         let mut p = Parser::new("{ foo = [\"bar\"] }");
-        assert_eq!("{ foo = [ \"bar\" ] }", p.finish_dictionary().map(|d| d.to_string()).unwrap());
+        assert_eq!("{ foo = [ \"bar\" ] }", p.finish_dictionary::<Owned>().map(|d| d.to_string()).unwrap());
     }
 
     #[test]
-    fn slice_to_inc() {
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some("foOb"), p.slice_to_inc('b'));
-        assert_eq!(Some((4, 'a')), p.cur.next());
+    fn number() {
+        let mut p = Parser::new("42");
+        assert_eq!(Some(Value::Integer(42)), p.value());
 
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some("f"), p.slice_to_inc('f'));
-        assert_eq!(Some((1, 'o')), p.cur.next());
+        let mut p = Parser::new("-3");
+        assert_eq!(Some(Value::Integer(-3)), p.value());
+
+        let mut p = Parser::new("+2");
+        assert_eq!(Some(Value::Integer(2)), p.value());
+
+        let mut p = Parser::new("-4.5");
+        assert_eq!(Some(Value::Float(-4.5)), p.value());
+
+        let mut p = Parser::new("1.5e-3");
+        assert_eq!(Some(Value::Float(1.5e-3)), p.value());
+
+        let mut p = Parser::new("2E+2");
+        assert_eq!(Some(Value::Float(200.0)), p.value());
+
+        let mut p = Parser::new("1e3");
+        assert_eq!(Some(Value::Float(1000.0)), p.value());
+    }
+
+    #[test]
+    fn date_time() {
+        let mut p = Parser::new("2026-10-17");
+        assert_eq!(Some(Value::Date(Date::new(2026, 10, 17).unwrap())), p.value());
+
+        let mut p = Parser::new("14:30:00");
+        assert_eq!(Some(Value::Time(Time::new(14, 30, 0, 0).unwrap())), p.value());
+
+        let mut p = Parser::new("2026-10-17T14:30:00+02:00");
+        let expected = DateTime::new(Date::new(2026, 10, 17).unwrap(), Time::new(14, 30, 0, 0).unwrap(), Some(120));
+        assert_eq!(Some(Value::DateTime(expected.unwrap())), p.value());
+
+        let mut p = Parser::new("[2026-10-17, 2026-10-18]");
+        assert_eq!("[ 2026-10-17, 2026-10-18 ]", p.value().unwrap().to_string());
+
+        let mut p = Parser::new("2026-02-30");
+        assert_eq!(None, p.value());
+        assert_eq!(1, p.errors.len());
+
+        let mut p = Parser::new("2026");
+        assert_eq!(Some(Value::Integer(2026)), p.value());
+    }
+
+    #[test]
+    fn null() {
+        let mut p = Parser::new("key = null\nary = [1, null]");
+        assert_eq!(Some(Entry("key".to_owned(), Value::Null)), p.next());
+        assert_eq!(Some(Entry("ary".to_owned(), Value::Array(vec![Value::Integer(1), Value::Null]))), p.next());
+
+        let mut p = Parser::new("nul");
+        assert_eq!(None, p.value());
+        assert_eq!(1, p.errors.len());
+    }
+
+    #[test]
+    fn key_name() {
+        let mut p = Parser::new(r#""check-in time" = 1"#);
+        assert_eq!(Some(Entry("check-in time".to_owned(), Value::Integer(1))), p.next());
+
+        let mut p = Parser::new(r#""a.b" = 1"#);
+        assert_eq!(Some(Entry("a.b".to_owned(), Value::Integer(1))), p.next());
+
+        let mut p = Parser::new(r#""say \"hi\"" = 1"#);
+        assert_eq!(Some(Entry("say \"hi\"".to_owned(), Value::Integer(1))), p.next());
+
+        let mut p = Parser::new("näme = 1");
+        assert_eq!(Some(Entry("näme".to_owned(), Value::Integer(1))), p.next());
+
+        let mut p = Parser::new(r#"dict = { "a b" = 1, ключ = 2 }"#);
+        let mut dict = Dictionary::new();
+        dict.insert("a b".to_owned(), Value::Integer(1));
+        dict.insert("ключ".to_owned(), Value::Integer(2));
+        assert_eq!(Some(Entry("dict".to_owned(), Value::Dictionary(dict))), p.next());
+    }
+
+    #[test]
+    fn key_name_errors() {
+        let mut p = Parser::new("a.b = 1");
+        assert_eq!(None, p.next());
+        assert_eq!("Expected '='", p.errors[0].desc);
+        assert_eq!(1, p.errors[0].lo);
+
+        let mut p = Parser::new("first = 1\n!second = 2");
+        assert_eq!(Some(Entry("first".to_owned(), Value::Integer(1))), p.next());
+        assert_eq!(None, p.next());
+        assert_eq!("Cannot read a key, expected a letter, digit, '_', '-' or a quoted key", p.errors[0].desc);
+        assert_eq!(10, p.errors[0].lo);
+    }
+
+    #[test]
+    fn key_round_trip() {
+        let mut section = Section::new();
+        for key in &["plain_key-1", "näme", "check-in time", "a.b", "", "quote\"key"] {
+            section.dictionary.insert(key.to_string(), Value::Integer(1));
+        }
+        let mut dict = Dictionary::new();
+        dict.insert("a b".to_owned(), Value::Integer(2));
+        section.dictionary.insert("dict".to_owned(), Value::Dictionary(dict));
+
+        let raw = section.to_string();
+        assert!(raw.contains("\nplain_key-1 = 1\n"));
+        assert!(raw.contains("\nnäme = 1\n"));
+        assert!(raw.contains("\n\"check-in time\" = 1\n"));
+        assert!(raw.contains("dict = { \"a b\" = 2 }"));
+
+        let mut p = Parser::new(&raw);
+        assert_eq!(Some(section), p.read().unwrap().remove("root"));
     }
 
     #[test]
-    fn slice_to_exc() {
+    fn number_with_radix_and_separators() {
+        let mut p = Parser::new("0xff");
+        assert_eq!(Some(Value::Integer(255)), p.value());
+
+        let mut p = Parser::new("-0xDEAD_beef");
+        assert_eq!(Some(Value::Integer(-0xdead_beef)), p.value());
+
+        let mut p = Parser::new("0o755");
+        assert_eq!(Some(Value::Integer(0o755)), p.value());
+
+        let mut p = Parser::new("0b1010_0101");
+        assert_eq!(Some(Value::Integer(0b1010_0101)), p.value());
+
+        let mut p = Parser::new("1_000_000");
+        assert_eq!(Some(Value::Integer(1_000_000)), p.value());
+
+        let mut p = Parser::new("1_000.000_1e1_0");
+        assert_eq!(Some(Value::Float(1_000.000_1e10)), p.value());
+
+        let mut p = Parser::new("-9223372036854775808");
+        assert_eq!(Some(Value::Integer(i64::MIN)), p.value());
+
+        let mut p = Parser::new("0x7fff_ffff_ffff_ffff");
+        assert_eq!(Some(Value::Integer(i64::MAX)), p.value());
+    }
+
+    #[test]
+    fn number_with_radix_and_separators_errors() {
+        let mut p = Parser::new("key = 9223372036854775808");
+        assert_eq!(None, p.next());
+        assert_eq!("Integer is out of range for a 64-bit signed integer", p.errors[0].desc);
+        assert_eq!((6, 25), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new("0x8000_0000_0000_0000");
+        assert_eq!(None, p.value());
+        assert_eq!("Integer is out of range for a 64-bit signed integer", p.errors[0].desc);
+
+        let mut p = Parser::new("0x");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a number, a digit is expected", p.errors[0].desc);
+
+        for raw in &["1__000", "1_000_", "1_.5", "0x_ff"] {
+            let mut p = Parser::new(raw);
+            assert_eq!(None, p.value(), "{}", raw);
+            assert_eq!(1, p.errors.len(), "{}", raw);
+        }
+
+        let mut p = Parser::new("1__000");
+        p.value();
+        assert_eq!("Cannot read a number, '_' is only allowed between digits", p.errors[0].desc);
+        assert_eq!((0, 6), (p.errors[0].lo, p.errors[0].hi));
+    }
+
+    #[test]
+    fn number_errors() {
+        let mut p = Parser::new(".5");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a number, a leading digit is required before '.'", p.errors[0].desc);
+
+        let mut p = Parser::new("1.");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a number, a digit is expected after '.'", p.errors[0].desc);
+
+        let mut p = Parser::new("1e");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a number, a digit is expected in the exponent", p.errors[0].desc);
+
+        let mut p = Parser::new("-");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a number, a digit is expected", p.errors[0].desc);
+    }
+
+    #[test]
+    fn slice_to_inc() {
         let mut p = Parser::new("foObar");
-        assert_eq!(Some("foO"), p.slice_to_exc('b'));
-        assert_eq!(Some((4, 'a')), p.cur.next());
+        assert_eq!(Some("foOb"), p.slice_to_inc('b'));
+        assert_eq!((4, Some('a')), (p.pos(), p.peek()));
 
         let mut p = Parser::new("foObar");
-        assert_eq!(Some(""), p.slice_to_exc('f'));
-        assert_eq!(Some((1, 'o')), p.cur.next());
+        assert_eq!(Some("f"), p.slice_to_inc('f'));
+        assert_eq!((1, Some('o')), (p.pos(), p.peek()));
     }
 
     #[test]
     fn slice_while() {
         let mut p = Parser::new("foObar");
         assert_eq!(Some("foO"), p.slice_while(|c| c != 'b'));
-        assert_eq!(Some((3, 'b')), p.cur.next());
+        assert_eq!((3, Some('b')), (p.pos(), p.peek()));
 
         let mut p = Parser::new("foObar");
         assert_eq!(None, p.slice_while(|c| c != 'f'));
-        assert_eq!(Some((0, 'f')), p.cur.next());
+        assert_eq!((0, Some('f')), (p.pos(), p.peek()));
     }
 
     #[test]
@@ -667,8 +2128,58 @@ mod tests {
         assert_eq!(format!("{}", Value::String("foo".to_owned())), "foo");
         assert_eq!(format!("{}", Value::Integer(1)), "1");
         assert_eq!(format!("{}", Value::Boolean(true)), "true");
+        assert_eq!(format!("{}", Value::Null), "null");
         let ary = Value::Array(vec![Value::Integer(1), Value::String("foo".to_owned())]);
         assert_eq!(format!("{}", ary), "[ 1, \"foo\" ]");
+        assert_eq!(format!("{}", Value::Integer(-3)), "-3");
+        assert_eq!(format!("{}", Value::Float(-0.5)), "-0.5");
+        assert_eq!(format!("{}", Value::Float(1.5e-7)), "1.5e-7");
+        assert_eq!(format!("{}", Value::Float(2e20)), "2e20");
+    }
+
+    #[test]
+    fn display_number_round_trip() {
+        let values = [
+            Value::Integer(-3), Value::Float(-0.5), Value::Float(1.5e-7), Value::Float(-2e20),
+            Value::Float(4.0), Value::Float(-0.0), Value::Float(0.1 + 0.2), Value::Float(f64::MAX),
+            Value::Float(f64::MIN_POSITIVE), Value::Float(f64::INFINITY), Value::Float(f64::NEG_INFINITY),
+        ];
+        for v in &values {
+            let raw = format!("key = {}", v);
+            let mut p = Parser::new(&raw);
+            match p.next() {
+                Some(Entry(_, Value::Float(f))) => assert_eq!(v.as_float().unwrap().to_bits(), f.to_bits(), "{}", raw),
+                actual => assert_eq!(Some(Entry("key".to_owned(), v.clone())), actual),
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn display_float_round_trip(f: f64) -> bool {
+        let raw = format!("key = {}", Value::Float(f));
+        match Parser::new(&raw).next() {
+            Some(Entry(_, Value::Float(parsed))) => parsed.to_bits() == f.to_bits() || (f.is_nan() && parsed.is_nan()),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn special_floats() {
+        let mut p = Parser::new("[inf, +inf, -inf, nan, -nan]");
+        let ary = p.value().unwrap();
+        let floats: Vec<f64> = ary.as_array().unwrap().iter().map(|v| v.as_float().unwrap()).collect();
+        assert_eq!(vec![f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY], floats[..3].to_vec());
+        assert!(floats[3].is_nan() && floats[3].is_sign_positive());
+        assert!(floats[4].is_nan() && floats[4].is_sign_negative());
+
+        assert_eq!("[ inf, -inf, nan ]", Value::Array(vec![
+            Value::Float(f64::INFINITY), Value::Float(f64::NEG_INFINITY), Value::Float(f64::NAN)
+        ]).to_string());
+        assert_eq!("4.0", Value::Float(4.0).to_string());
+
+        let mut p = Parser::new("invalid");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a value", p.errors[0].desc);
     }
 
     mod read {
@@ -895,6 +2406,60 @@ mod tests {
                         expected.insert("SECTION".to_owned(), section);
                         assert_eq!(expected, actual);
                     }
+
+                    const RAW: &str = r#"
+                        [SECTION]
+                        key = "1value"
+                        1key = "1value"
+                        | 1col1 | 1col2|
+                        [OTHER]
+                        [SECTION]
+                        key = "2value"
+                        | 2col1 | 2col2|
+                    "#;
+
+                    fn section(mut p: Parser) -> Section {
+                        let mut map = p.read().expect("Read failed");
+                        map.remove("SECTION").unwrap()
+                    }
+
+                    #[test]
+                    fn then_keep_first_returns_first_occurance_of_section() {
+                        let actual = section(Parser::new(RAW).with_duplicate_sections(DuplicateSections::KeepFirst));
+
+                        assert_eq!(Some(&Value::String("1value".to_owned())), actual.get("key"));
+                        assert_eq!(vec![vec![Value::String("1col1".to_owned()), Value::String("1col2".to_owned())]], actual.rows);
+                    }
+
+                    #[test]
+                    fn then_keep_last_returns_last_occurance_of_section() {
+                        let actual = section(Parser::new(RAW).with_duplicate_sections(DuplicateSections::KeepLast));
+
+                        assert_eq!(Some(&Value::String("2value".to_owned())), actual.get("key"));
+                        assert_eq!(None, actual.get("1key"));
+                        assert_eq!(1, actual.rows.len());
+                    }
+
+                    #[test]
+                    fn then_merge_returns_merged_sections() {
+                        let actual = section(Parser::new(RAW).with_duplicate_sections(DuplicateSections::Merge));
+
+                        assert_eq!(Some(&Value::String("2value".to_owned())), actual.get("key"));
+                        assert_eq!(Some(&Value::String("1value".to_owned())), actual.get("1key"));
+                        assert_eq!(vec![Value::String("1col1".to_owned()), Value::String("1col2".to_owned())], actual.rows[0]);
+                        assert_eq!(vec![Value::String("2col1".to_owned()), Value::String("2col2".to_owned())], actual.rows[1]);
+                    }
+
+                    #[test]
+                    fn then_error_reports_both_positions() {
+                        let mut p = Parser::new(RAW).with_duplicate_sections(DuplicateSections::Error);
+
+                        assert_eq!(None, p.read());
+                        assert_eq!(1, p.errors.len());
+                        assert_eq!("Duplicate section 'SECTION', first defined at 2:25", p.errors[0].desc);
+                        assert_eq!("[SECTION]", &RAW[p.errors[0].lo..p.errors[0].hi]);
+                        assert!(p.errors[0].lo > 34);
+                    }
                 }
             }
         }
@@ -1049,6 +2614,21 @@ mod tests {
                             expected.insert("ACCEPTED".to_owned(), section);
                             assert_eq!(expected, actual);
                         }
+
+                        #[test]
+                        fn then_policy_applies_to_accepted_section() {
+                            let raw = r#"
+                                [ACCEPTED]
+                                1key = "1value"
+                                [ACCEPTED]
+                                2key = "2value"
+                            "#;
+                            let mut p = Parser::new_filtered(raw, vec!["ACCEPTED"]).with_duplicate_sections(DuplicateSections::Merge);
+
+                            let actual = p.read().expect("Read failed");
+
+                            assert_eq!(2, actual["ACCEPTED"].dictionary.len());
+                        }
                     }
 
                     mod and_it_is_not_the_only_accepted_section {
@@ -1128,6 +2708,317 @@ mod tests {
                     }
                 }
             }
+
+            mod and_filtered_section_has_brackets_and_multi_byte_characters {
+                use super::*;
+
+                #[test]
+                fn then_skips_to_the_next_header() {
+                    let raw = "[FILTERED]\nkey = [[1], \"Zürich [x]\"]\n| [a] | ü |\n\t [ACCEPTED]\ncity = \"Zürich\" # [x]\n| Zürich | ü |\n";
+                    let mut p = Parser::new_filtered(raw, vec!["ACCEPTED"]);
+
+                    let actual = p.read().expect("Read failed");
+
+                    let mut section = Section::new();
+                    section.dictionary.insert("city".to_owned(), Value::new_string("Zürich"));
+                    section.rows.push(vec![Value::new_string("Zürich"), Value::new_string("ü")]);
+                    assert_eq!(Some(&section), actual.get("ACCEPTED"));
+                    assert_eq!(1, actual.len());
+                }
+            }
+
+            mod and_filtered_section_has_values_spanning_lines {
+                use super::*;
+
+                #[test]
+                fn then_skips_over_lines_of_strings_and_arrays_starting_with_brackets() {
+                    let raw = concat!(
+                        "[FILTERED]\n",
+                        "text = \"\"\"\n[ACCEPTED]\nfake = 1\n\"\"\"\n",
+                        "raw = \'\'\'\n  [ACCEPTED]\n\'\'\'\n",
+                        "array = [\n[1, 2],\n  [3]\n]\n",
+                        "[ACCEPTED]\n",
+                        "real = 2\n",
+                    );
+                    let mut p = Parser::new_filtered(raw, vec!["ACCEPTED"]);
+
+                    let actual = p.read().expect("Read failed");
+
+                    let mut section = Section::new();
+                    section.dictionary.insert("real".to_owned(), Value::Integer(2));
+                    assert_eq!(Some(&section), actual.get("ACCEPTED"));
+                    assert_eq!(1, actual.len());
+                    assert!(p.errors.is_empty());
+                }
+            }
+        }
+    }
+
+    struct MemoryResolver(BTreeMap<&'static str, &'static str>);
+
+    impl Resolver for MemoryResolver {
+        fn resolve(&self, path: &str, _from: Option<&str>) -> io::Result<(String, String)> {
+            match self.0.get(path) {
+                Some(content) => Ok((path.to_owned(), content.to_string())),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "not found")),
+            }
+        }
+    }
+
+    fn resolver(files: &[(&'static str, &'static str)]) -> MemoryResolver {
+        MemoryResolver(files.iter().cloned().collect())
+    }
+
+    #[test]
+    fn include() {
+        let mut p = Parser::new("#include \"a.ion\"\n# include is not a directive\n#includes\n");
+        assert_eq!(Some(Element::Include("a.ion".to_owned())), p.next());
+        assert_eq!(Some(Comment(" include is not a directive\n".to_owned())), p.next());
+        assert_eq!(Some(Comment("includes\n".to_owned())), p.next());
+    }
+
+    #[test]
+    fn read_with_resolver() {
+        let files = resolver(&[
+            ("common.ion", "[COMMON]\nrate = 2\n#include \"nested.ion\"\nafter = 1"),
+            ("nested.ion", "[NESTED]\nx = 1"),
+        ]);
+        let mut p = Parser::new("[MAIN]\nfoo = 1\n#include \"common.ion\"\n[LAST]\nbar = 2");
+        let map = p.read_with_resolver(&files).unwrap();
+
+        assert_eq!(vec!["COMMON", "LAST", "MAIN", "NESTED"], map.keys().collect::<Vec<_>>());
+        assert_eq!(Some(&Value::Integer(1)), map["NESTED"].get("after"));
+        assert_eq!(Some(&Value::Integer(2)), map["LAST"].get("bar"));
+
+        let mut p = Parser::new("[MAIN]\n#include \"nested.ion\"\n\n[NESTED]").with_duplicate_sections(DuplicateSections::Error);
+        assert_eq!(None, p.read_with_resolver(&files));
+        assert_eq!("Duplicate section 'NESTED', first defined at nested.ion:1:1", p.errors[0].desc);
+        assert_eq!(4, p.errors[0].line);
+    }
+
+    #[test]
+    fn include_errors() {
+        let mut p = Parser::new("#include \"a.ion\"");
+        assert_eq!(None, p.read());
+        assert_eq!("Cannot include 'a.ion' without a resolver", p.errors[0].desc);
+        assert_eq!((0, 16), (p.errors[0].lo, p.errors[0].hi));
+
+        let files = resolver(&[("a.ion", "[A]\nx = [1,"), ("b.ion", "#include \"c.ion\""), ("c.ion", "#include \"b.ion\"")]);
+        let mut p = Parser::new("#include \"a.ion\"\n#include \"missing.ion\"").with_file_name("main.ion");
+        assert_eq!(None, p.read_with_resolver(&files));
+        assert_eq!("Cannot finish an array", p.errors[0].desc);
+        assert_eq!(Some("a.ion".to_owned()), p.errors[0].file);
+        assert_eq!("Cannot include 'missing.ion': not found", p.errors[1].desc);
+        assert_eq!(Some("main.ion".to_owned()), p.errors[1].file);
+
+        let mut p = Parser::new("#include \"b.ion\"");
+        assert_eq!(None, p.read_with_resolver(&files));
+        assert_eq!(1, p.errors.len());
+        assert_eq!("Cannot include 'b.ion', it is already being included", p.errors[0].desc);
+        assert_eq!(Some("c.ion".to_owned()), p.errors[0].file);
+    }
+
+    #[test]
+    fn reference() {
+        let mut p = Parser::new("a = ${HOTEL.name}\nb = [${ x }]\n");
+        assert_eq!(Some(Entry("a".to_owned(), Value::Reference("HOTEL.name".to_owned()))), p.next());
+        assert_eq!(Some(Entry("b".to_owned(), Value::Array(vec![Value::Reference("x".to_owned())]))), p.next());
+        assert_eq!("${HOTEL.name}", Value::Reference("HOTEL.name".to_owned()).to_string());
+
+        for raw in &["a = ${}", "a = ${x", "a = $x"] {
+            let mut p = Parser::new(raw);
+            assert_eq!(None, p.next());
+            assert_eq!("Cannot read a reference, expected '${SECTION.key}'", p.errors[0].desc);
+        }
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let raw = "[A]\nprice = 1\nprice = 2\nd = { a = 1, a = 2 }\n[B]\nprice = 3";
+
+        let mut p = Parser::new(raw);
+        assert_eq!(Some(&Value::Integer(2)), p.read().unwrap()["A"].get("price"));
+        assert!(p.warnings.is_empty());
+
+        let mut p = Parser::new(raw).with_duplicate_keys(DuplicateKeys::Warn);
+        let map = p.read().unwrap();
+        assert_eq!(Some(&Value::Integer(2)), map["A"].get("price"));
+        assert_eq!(2, p.warnings.len());
+        assert_eq!("Duplicate key 'price', first defined at 2:1", p.warnings[0].desc);
+        assert_eq!("price = 2", &raw[p.warnings[0].lo..p.warnings[0].hi]);
+        assert_eq!("Duplicate key 'a', first defined at 4:7", p.warnings[1].desc);
+        assert_eq!("a = 2", &raw[p.warnings[1].lo..p.warnings[1].hi]);
+
+        let mut p = Parser::new(raw).with_duplicate_keys(DuplicateKeys::Error);
+        assert_eq!(None, p.read());
+        assert_eq!(2, p.errors.len());
+        assert!(p.warnings.is_empty());
+    }
+
+    #[test]
+    fn duplicate_keys_in_merged_sections() {
+        let raw = "[A]\nprice = 1\n[B]\nprice = 1\n[A]\nprice = 2\n";
+
+        let mut p = Parser::new(raw).with_duplicate_sections(DuplicateSections::Merge).with_duplicate_keys(DuplicateKeys::Warn);
+        assert_eq!(Some(&Value::Integer(2)), p.read().unwrap()["A"].get("price"));
+        assert_eq!(1, p.warnings.len());
+        assert_eq!("Duplicate key 'price', first defined at 2:1", p.warnings[0].desc);
+        assert_eq!("price = 2", &raw[p.warnings[0].lo..p.warnings[0].hi]);
+
+        let mut p = Parser::new(raw).with_duplicate_sections(DuplicateSections::Merge).with_duplicate_keys(DuplicateKeys::Error);
+        assert_eq!(None, p.read());
+        assert_eq!(1, p.errors.len());
+
+        let mut p = Parser::new(raw).with_duplicate_sections(DuplicateSections::KeepLast).with_duplicate_keys(DuplicateKeys::Error);
+        assert_eq!(Some(&Value::Integer(2)), p.read().unwrap()["A"].get("price"));
+    }
+
+    #[test]
+    fn recovery() {
+        let raw = "[A]\na = 1\nb = ?\nc = 3\n[B]\n= 4\nd = [1, 2\ne = 5\n[C]\nf = 6 7\ng = 8";
+
+        let mut p = Parser::new(raw);
+        assert_eq!(None, p.read());
+        assert_eq!(1, p.errors.len());
+
+        let mut p = Parser::new(raw).with_recovery();
+        let map = p.read().unwrap();
+        let errors: Vec<_> = p.errors.iter().map(|e| e.desc.as_str()).collect();
+        assert_eq!(vec!["Cannot read a value", "Cannot read a key, expected a letter, digit, '_', '-' or a quoted key",
+                        "Cannot read a value", "Expected '='"], errors);
+
+        assert_eq!(vec!["a", "c"], map["A"].dictionary.keys().collect::<Vec<_>>());
+        assert_eq!(vec!["e"], map["B"].dictionary.keys().collect::<Vec<_>>());
+        assert_eq!(vec!["f", "g"], map["C"].dictionary.keys().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn error_position_and_kind() {
+        let mut p = Parser::new("[A]\nname = \"Zürich\"\r\nkey = ?").with_file_name("a.ion");
+        assert_eq!(None, p.read());
+        let error = &p.errors[0];
+        assert_eq!((3, 7), (error.line, error.col));
+        assert_eq!(ParserErrorKind::InvalidValue, error.kind);
+        assert_eq!("a.ion:3:7: Cannot read a value [E0001]", error.to_string());
+
+        let mut p = Parser::new("{ \"é\" = \"\\q\" }");
+        assert_eq!(None, p.value());
+        assert_eq!((1, 10), (p.errors[0].line, p.errors[0].col));
+        assert_eq!(ParserErrorKind::InvalidEscape, p.errors[0].kind);
+        assert_eq!("1:10: Invalid escape sequence [E0008]", p.errors[0].to_string());
+
+        let mut p = Parser::new("é = 1\nkey 1");
+        assert_eq!(None, p.read());
+        assert_eq!(ParserErrorKind::MissingEquals, p.errors[0].kind);
+        assert_eq!((2, 5), (p.errors[0].line, p.errors[0].col));
+    }
+
+    #[test]
+    fn strict() {
+        let lenient = "a = \"foObar\nb = trueish\nc = 1 d = 2 # comment\n[S] x = 1\n";
+        let mut p = Parser::new(lenient);
+        assert!(p.read().is_some());
+
+        let mut p = Parser::new(lenient).strict().with_recovery();
+        let map = p.read().unwrap();
+        let errors: Vec<_> = p.errors.iter().map(|e| (e.kind, &lenient[e.lo..e.hi])).collect();
+        assert_eq!(vec![
+            (ParserErrorKind::UnterminatedString, "\"foObar"),
+            (ParserErrorKind::InvalidValue, "trueish"),
+            (ParserErrorKind::TrailingCharacters, "d = 2 # comment"),
+            (ParserErrorKind::TrailingCharacters, "x = 1"),
+        ], errors);
+        assert!(map["S"].dictionary.is_empty());
+
+        let mut p = Parser::new("a = 'raw").strict();
+        assert_eq!(None, p.read());
+        assert_eq!("Cannot finish a string, expected a closing '", p.errors[0].desc);
+
+        let mut p = Parser::new("a = \"\"\"\nmulti\n").strict();
+        assert_eq!(None, p.read());
+        assert_eq!(ParserErrorKind::UnterminatedString, p.errors[0].kind);
+
+        let mut p = Parser::new("a = \"\"\"\nmulti\n\"\"\" # ok\nb = [true, false] # ok\n[S] # ok").strict();
+        assert!(p.read().is_some());
+    }
+
+    #[test]
+    fn cell_type_inference() {
+        let raw = "| 1 | -2.5 | false | null |  | x |";
+
+        let mut p = Parser::new(raw);
+        assert_eq!(Some(Row(vec![Value::new_string("1"), Value::new_string("-2.5"), Value::new_string("false"),
+                                 Value::new_string("null"), Value::new_string(""), Value::new_string("x")])), p.next());
+
+        let mut p = Parser::new(raw).with_cell_type_inference();
+        assert_eq!(Some(Row(vec![Value::Integer(1), Value::Float(-2.5), Value::Boolean(false),
+                                 Value::Null, Value::Null, Value::new_string("x")])), p.next());
+        assert!(p.errors.is_empty());
+
+        let mut p = Parser::new("| 1 | true |\n|---|---|\n| 2 | false |").with_cell_type_inference();
+        assert_eq!(Some(Row(vec![Value::new_string("1"), Value::new_string("true")])), p.next());
+        assert_eq!(Some(Row(vec![Value::new_string("---"), Value::new_string("---")])), p.next());
+        assert_eq!(Some(Row(vec![Value::Integer(2), Value::Boolean(false)])), p.next());
+
+        assert_eq!(Some(Value::Integer(-31)), infer_cell("-0x1F"));
+        assert_eq!(Some(Value::Integer(1000)), infer_cell("+1_000"));
+        assert_eq!(Some(Value::Float(-1.5e-3)), infer_cell("-1.5E-3"));
+        assert_eq!(Some(Value::Float(f64::NEG_INFINITY)), infer_cell("-inf"));
+        for cell in &["1_", "1__0", "_1", "1.", ".5", "1e", "0x", "0b2", "+-1", "2020-01-01", "nul", "True", "9223372036854775808"] {
+            assert_eq!(None, infer_cell(cell), "{}", cell);
         }
     }
+
+    #[test]
+    fn events() {
+        use std::borrow::Cow;
+        use super::EventKind::*;
+
+        let input = "# head\r\n[A] \r\n\r\n  x = \"Zürich\"  # c\n| a | 1 |\n\t \n#include \"b.ion\"\ny = \"\"\"\n  two\n  \"\"\"";
+        let mut p = Parser::new(input);
+        let events: Vec<_> = p.events().collect();
+        let spans: Vec<_> = events.iter().map(|e| (&input[e.lo..e.hi], e.line, e.col)).collect();
+        assert_eq!(vec![
+            ("# head", 1, 1),
+            ("[A]", 2, 1),
+            ("", 3, 1),
+            ("x = \"Zürich\"", 4, 3),
+            ("# c", 4, 17),
+            ("| a | 1 |", 5, 1),
+            ("", 6, 1),
+            ("#include \"b.ion\"", 7, 1),
+            ("y = \"\"\"\n  two\n  \"\"\"", 8, 1),
+        ], spans);
+
+        let kinds: Vec<_> = events.into_iter().map(|e| e.kind).collect();
+        assert_eq!(Comment(" head"), kinds[0]);
+        assert_eq!(Section(Cow::Borrowed("A")), kinds[1]);
+        assert_eq!(BlankLine, kinds[2]);
+        assert_eq!(Entry(Cow::Borrowed("x"), ValueRef::String(Cow::Borrowed("Zürich"))), kinds[3]);
+        assert_eq!(Row(vec![ValueRef::String(Cow::Borrowed("a")), ValueRef::String(Cow::Borrowed("1"))]), kinds[5]);
+        assert_eq!(Include(Cow::Borrowed("b.ion")), kinds[7]);
+        assert_eq!(Some("two"), match kinds[8] { Entry(_, ref v) => v.as_str(), _ => None });
+        assert!(p.errors.is_empty());
+
+        let mut p = Parser::new("x = 1\ny = ?\nz = 3\n");
+        assert_eq!(1, p.events().count());
+        assert_eq!(1, p.errors.len());
+
+        let mut p = Parser::new("x = 1\ny = ?\nz = 3\n").with_recovery();
+        let lines: Vec<_> = p.events().map(|e| e.line).collect();
+        assert_eq!(vec![1, 3], lines);
+        assert_eq!(2, p.errors[0].line);
+
+        let mut p = Parser::new("[A]\nx = 1 # one\n  \t");
+        assert!(p.next().is_some());
+        let events: Vec<_> = p.events().map(|e| (e.line, e.col, e.kind)).collect();
+        assert_eq!(vec![
+            (2, 1, Entry(Cow::Borrowed("x"), ValueRef::Integer(1))),
+            (2, 7, Comment(" one")),
+            (3, 1, BlankLine),
+        ], events);
+
+        let mut p = Parser::new("x = 1\n");
+        assert_eq!(1, p.events().count());
+    }
 }
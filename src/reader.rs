@@ -0,0 +1,313 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io::BufRead;
+use std::mem;
+use split::{section_name, Line, Splitter};
+use parser::offset_errors;
+use {DuplicateKeys, EventKind, IonError, Parser, ParserError, ParserErrorKind, Section};
+
+/// Reads ion text from a `BufRead` one section at a time, so that only the section being read
+/// is held in memory. Yields each section as `(name, section)` when its last line was read, a
+/// section defined more than once is yielded once for each definition.
+///
+/// Unlike `Parser::read`, entries and rows before the first section are yielded as the "root"
+/// section even if sections follow, and `#include` directives are reported as errors. A section
+/// with errors is yielded as `IonError::ParserErrors`, with the positions in the whole input,
+/// and reading continues with the next section.
+///
+/// ```
+/// use ion::SectionReader;
+///
+/// let input = "[A]\nx = 1\n[B]\n| 1 | 2 |\n| 3 | 4 |\n";
+/// let mut sections = SectionReader::new(input.as_bytes()).with_max_rows(1);
+///
+/// let (name, a) = sections.next().unwrap().unwrap();
+/// assert_eq!(("A", 1), (name.as_str(), a.dictionary.len()));
+/// let (name, b) = sections.next().unwrap().unwrap();
+/// assert_eq!(("B", 1), (name.as_str(), b.rows.len()));
+/// let (name, b) = sections.next().unwrap().unwrap();
+/// assert_eq!(("B", 1), (name.as_str(), b.rows.len()));
+/// assert!(sections.next().is_none());
+/// ```
+pub struct SectionReader<R> {
+    reader: R,
+    splitter: Splitter,
+    // the line that starts the next chunk, already read
+    pending: String,
+    pending_kind: Line,
+    // the section being read, `None` before the first section
+    section: Option<String>,
+    // the sections of a chunk with more than one, in the order of the input
+    queued: VecDeque<(String, Section)>,
+    // the byte offset and the number of lines before the next chunk
+    offset: usize,
+    line: usize,
+    done: bool,
+    file: Option<String>,
+    max_rows: Option<usize>,
+    duplicate_keys: DuplicateKeys,
+    strict: bool,
+    infer_cells: bool,
+}
+
+impl<R: BufRead> SectionReader<R> {
+    pub fn new(reader: R) -> SectionReader<R> {
+        SectionReader {
+            reader,
+            splitter: Splitter::new(),
+            pending: String::new(),
+            pending_kind: Line::Other,
+            section: None,
+            queued: VecDeque::new(),
+            offset: 0,
+            line: 0,
+            done: false,
+            file: None,
+            max_rows: None,
+            duplicate_keys: DuplicateKeys::Ignore,
+            strict: false,
+            infer_cells: false,
+        }
+    }
+
+    /// Names the file the input is read from, see `Parser::with_file_name`
+    pub fn with_file_name<S: Into<String>>(mut self, file: S) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Yields a section with more than `max_rows` rows in parts of up to `max_rows` rows, each
+    /// under the section's name. The first part has the entries before the first row, later
+    /// parts the entries between their rows. Keeps the memory bounded for large tables.
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows.max(1));
+        self
+    }
+
+    /// See `Parser::with_duplicate_keys`, the warnings are dropped
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// See `Parser::strict`
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// See `Parser::with_cell_type_inference`
+    pub fn with_cell_type_inference(mut self) -> Self {
+        self.infer_cells = true;
+        self
+    }
+
+    // reads the lines up to the next section or, with `max_rows`, up to the row that starts
+    // the next part of a table. Returns whether the chunk starts with a section header.
+    fn chunk(&mut self) -> Result<(String, bool), ParserError> {
+        let mut starts_section = self.pending_kind == Line::Section;
+        let mut rows = if self.pending_kind == Line::Row { 1 } else { 0 };
+        let mut chunk = mem::take(&mut self.pending);
+        self.pending_kind = Line::Other;
+
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                },
+                Ok(_) => (),
+                Err(e) => return Err(self.io_error(&chunk, &e.to_string())),
+            }
+
+            let kind = self.splitter.line(&line);
+            let split = match kind {
+                Line::Section => true,
+                Line::Row => self.max_rows.map_or(false, |max| rows >= max),
+                Line::Other => false,
+            };
+            if split && !chunk.trim().is_empty() {
+                self.pending = line;
+                self.pending_kind = kind;
+                break;
+            }
+
+            match kind {
+                Line::Section => starts_section = true,
+                Line::Row => rows += 1,
+                Line::Other => (),
+            }
+            chunk.push_str(&line);
+        }
+        Ok((chunk, starts_section))
+    }
+
+    fn parse(&self, chunk: &str) -> (Option<BTreeMap<String, Section>>, Vec<ParserError>) {
+        let mut parser = Parser::new(chunk).with_duplicate_keys(self.duplicate_keys);
+        if let Some(ref file) = self.file { parser = parser.with_file_name(file.clone()) }
+        if self.strict { parser = parser.strict() }
+        if self.infer_cells { parser = parser.with_cell_type_inference() }
+
+        let sections = parser.read();
+        let mut errors = parser.errors;
+        offset_errors(&mut errors, self.offset, self.line);
+        (sections, errors)
+    }
+
+    // queues the sections of a chunk in which the parser found headers the `Splitter` does
+    // not see, like one after an entry on the same line, in the order of their headers
+    fn queue(&mut self, chunk: &str, mut sections: BTreeMap<String, Section>) {
+        let mut parser = Parser::new(chunk);
+        for event in parser.events() {
+            if let EventKind::Section(name) = event.kind {
+                if let Some(section) = sections.remove(&*name) {
+                    self.queued.push_back((name.into_owned(), section));
+                }
+            }
+        }
+        self.section = self.queued.back().map(|(name, _)| name.clone());
+    }
+
+    // an error reading the line after `chunk`
+    fn io_error(&self, chunk: &str, desc: &str) -> ParserError {
+        let pos = self.offset + chunk.len();
+        ParserError {
+            lo: pos, hi: pos,
+            line: self.line + chunk.matches('\n').count() + 1, col: 1,
+            kind: ParserErrorKind::Io,
+            desc: format!("Cannot read the input: {}", desc),
+            file: self.file.clone(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SectionReader<R> {
+    type Item = Result<(String, Section), IonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(section) = self.queued.pop_front() { return Some(Ok(section)) }
+            if self.done && self.pending.is_empty() { return None }
+
+            let (chunk, starts_section) = match self.chunk() {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    self.done = true;
+                    self.pending.clear();
+                    return Some(Err(IonError::ParserErrors(vec![error])));
+                },
+            };
+            if starts_section {
+                self.section = Some(section_name(&chunk).to_owned());
+            }
+
+            let (sections, errors) = self.parse(&chunk);
+            self.offset += chunk.len();
+            self.line += chunk.matches('\n').count();
+
+            if !errors.is_empty() {
+                return Some(Err(IonError::ParserErrors(errors)));
+            }
+            let sections = match sections {
+                Some(sections) => sections,
+                None => continue,
+            };
+            if sections.len() > 1 {
+                self.queue(&chunk, sections);
+                continue;
+            }
+            let section = match sections.into_iter().next() {
+                Some((_, section)) => section,
+                None => continue,
+            };
+            match self.section {
+                Some(ref name) => return Some(Ok((name.clone(), section))),
+                None if section.dictionary.is_empty() && section.rows.is_empty() => continue,
+                None => return Some(Ok(("root".to_owned(), section))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SectionReader;
+    use {Ion, IonError, Parser, Section};
+
+    const RAW: &str = "x = 0\n\n[A]\nname = \"a\"\ntext = \"\"\"\n[not a section]\n\"\"\"\n\n[B]\n| 1 | 2 |\n|---|---|\n| 3 | 4 |\nafter = [\n  [1], 2]\n| 5 | 6 |\n[A]\nname = \"again\"\n";
+
+    fn read(reader: SectionReader<&[u8]>) -> Vec<(String, Section)> {
+        reader.map(|s| s.expect("a section")).collect()
+    }
+
+    #[test]
+    fn yields_each_section() {
+        let sections = read(SectionReader::new(RAW.as_bytes()));
+        let names: Vec<_> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(vec!["root", "A", "B", "A"], names);
+
+        let ion: Ion = RAW.parse().unwrap();
+        assert_eq!(Some(&sections[2].1), ion.get("B"));
+        assert_eq!(Some(&sections[3].1), ion.get("A"));
+        assert_eq!(Some("[not a section]"), sections[1].1.get("text").and_then(|v| v.as_str()));
+    }
+
+    #[test]
+    fn splits_large_tables() {
+        let sections = read(SectionReader::new(RAW.as_bytes()).with_max_rows(2));
+        let b: Vec<_> = sections.iter().filter(|s| s.0 == "B").map(|s| &s.1).collect();
+        assert_eq!(2, b.len());
+        assert_eq!(2, b[0].rows.len());
+        assert_eq!(2, b[1].rows.len());
+        assert!(b[0].dictionary.is_empty());
+        assert!(b[1].get("after").is_some());
+    }
+
+    #[test]
+    fn errors_have_positions_in_the_input() {
+        let input = "[A]\nx = 1\n[B]\ny = [1\n[C]\nz = 2\n";
+        let mut p = Parser::new(input);
+        p.read();
+
+        let results: Vec<_> = SectionReader::new(input.as_bytes()).collect();
+        assert_eq!(2, results.len());
+        match results[1] {
+            Err(IonError::ParserErrors(ref errors)) => {
+                assert_eq!(p.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+                           errors.iter().map(|e| e.to_string()).collect::<Vec<_>>());
+                assert_eq!((p.errors[0].lo, p.errors[0].hi), (errors[0].lo, errors[0].hi));
+            },
+            ref r => panic!("unexpected {:?}", r),
+        }
+
+        let results: Vec<_> = SectionReader::new("[A]\nx = 1\n[B]\ny = ?\n[C]\nz = 2\n".as_bytes()).collect();
+        assert_eq!(3, results.len());
+        assert!(results[1].is_err());
+        assert_eq!("C", results[2].as_ref().unwrap().0);
+    }
+
+    #[test]
+    fn header_after_an_entry() {
+        let input = "[Z]\nx = 1 [B]\ny = 2\n| 1 |\n| 2 |\n";
+        let ion: Ion = input.parse().unwrap();
+
+        let sections = read(SectionReader::new(input.as_bytes()));
+        let names: Vec<_> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(vec!["Z", "B"], names);
+        assert_eq!(ion.get("Z"), Some(&sections[0].1));
+        assert_eq!(ion.get("B"), Some(&sections[1].1));
+
+        let sections = read(SectionReader::new(input.as_bytes()).with_max_rows(1));
+        let names: Vec<_> = sections.iter().map(|s| s.0.as_str()).collect();
+        assert_eq!(vec!["Z", "B", "B"], names);
+    }
+
+    #[test]
+    fn reports_invalid_utf8() {
+        let input: &[u8] = b"[A]\nx = 1\ny = \"\xff\"\n";
+        match SectionReader::new(input).next() {
+            Some(Err(IonError::ParserErrors(errors))) => assert_eq!(3, errors[0].line),
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+}
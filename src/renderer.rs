@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use {IonError, ParserError, ParserErrorKind};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders `ParserError`s like a compiler does, with the position, the line of the source
+/// the error is in and a hint where there is one:
+///
+/// ```text
+/// error[E0004]: Cannot finish an array
+///  --> hotel.ion:3:17
+///   |
+/// 3 | markets = ["abc"
+///   |                 ^
+///   = hint: close the array with `]`
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    /// Colors the output with ANSI escape codes
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Renders all errors of `IonError::ParserErrors`, separated by empty lines. `sources`
+    /// returns the text of the file an error is in by its `ParserError::file`, which is `None`
+    /// for an input without a file name. Errors in files it returns `None` for, and errors
+    /// other than parser errors, are rendered without a snippet.
+    ///
+    /// ```
+    /// # use ion::{Ion, Renderer};
+    /// let source = "[A]\nx = [1";
+    /// let error = source.parse::<Ion>().unwrap_err();
+    /// let rendered = Renderer::new().render(|file| if file.is_none() { Some(source.into()) } else { None }, &error);
+    /// assert!(rendered.starts_with("error[E0004]: Cannot finish an array\n --> 2:7\n"));
+    /// ```
+    pub fn render<'s, F>(&self, sources: F, error: &IonError) -> String
+        where F: Fn(Option<&str>) -> Option<Cow<'s, str>>
+    {
+        match *error {
+            IonError::ParserErrors(ref errors) => {
+                errors.iter().map(|e| {
+                    let source = sources(e.file.as_deref());
+                    self.render_in(source.as_deref(), e)
+                }).collect::<Vec<_>>().join("\n")
+            },
+            ref error => format!("{}\n", self.header("error", &error.to_string())),
+        }
+    }
+
+    /// Renders a single error, `source` must be the text of the file the error is in
+    pub fn render_error(&self, source: &str, error: &ParserError) -> String {
+        self.render_in(Some(source), error)
+    }
+
+    fn render_in(&self, source: Option<&str>, error: &ParserError) -> String {
+        let mut out = self.header(&format!("error[{}]", error.kind.code()), &error.desc);
+        let location = match error.file {
+            Some(ref file) => format!("{}:{}:{}", file, error.line, error.col),
+            None => format!("{}:{}", error.line, error.col),
+        };
+
+        let line_no = error.line.to_string();
+        let pad = " ".repeat(line_no.len());
+        let _ = write!(out, "\n{}{}-->{} {}\n", pad, self.paint(BLUE), self.reset(), location);
+
+        if let Some((line, indent, carets)) = source.and_then(|source| snippet(source, error.lo, error.hi)) {
+            let _ = writeln!(out, "{} {}|{}", pad, self.paint(BLUE), self.reset());
+            let _ = writeln!(out, "{}{} |{} {}", self.paint(BLUE), line_no, self.reset(), line);
+            let _ = writeln!(out, "{} {}|{} {}{}{}{}", pad, self.paint(BLUE), self.reset(), indent, self.paint(RED), carets, self.reset());
+        }
+        if let Some(hint) = hint(error.kind) {
+            let _ = writeln!(out, "{} {}={} {}hint{}: {}", pad, self.paint(BLUE), self.reset(), self.paint(CYAN), self.reset(), hint);
+        }
+        out
+    }
+
+    fn header(&self, label: &str, message: &str) -> String {
+        format!("{}{}{}: {}{}{}", self.paint(RED), label, self.reset(), self.paint(BOLD), message, self.reset())
+    }
+
+    fn paint(&self, code: &'static str) -> &'static str {
+        if self.color { code } else { "" }
+    }
+
+    fn reset(&self) -> &'static str {
+        self.paint(RESET)
+    }
+}
+
+// the line containing the byte `lo` of `source`, the indentation up to `lo` and `^`s under
+// `lo..hi`, at least one when the range is empty. Tabs are kept in the indentation so that
+// the `^`s line up with the source line.
+fn snippet(source: &str, lo: usize, hi: usize) -> Option<(&str, String, String)> {
+    let before = source.get(..lo)?;
+    let start = before.rfind('\n').map_or(0, |i| i + 1);
+    let end = source[lo..].find('\n').map_or(source.len(), |i| lo + i);
+    let line = source[start..end].trim_end_matches('\r');
+
+    let indent = source[start..lo].chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    let width = source.get(lo..hi.min(start + line.len()).max(lo)).map_or(0, |s| s.chars().count());
+    Some((line, indent, "^".repeat(width.max(1))))
+}
+
+fn hint(kind: ParserErrorKind) -> Option<&'static str> {
+    match kind {
+        ParserErrorKind::InvalidValue => {
+            Some("values are quoted strings, numbers, booleans, dates, `null`, `${SECTION.key}`, `[arrays]` or `{ dictionaries }`")
+        },
+        ParserErrorKind::InvalidKey => Some("keys with other characters have to be quoted, like `\"my key\" = 1`"),
+        ParserErrorKind::MissingEquals => Some("entries are written as `key = value`"),
+        ParserErrorKind::UnterminatedArray => Some("close the array with `]`"),
+        ParserErrorKind::UnterminatedDictionary => Some("close the dictionary with `}`"),
+        ParserErrorKind::IntegerOutOfRange => Some("use a float for larger numbers, like `1e20`"),
+        ParserErrorKind::UnterminatedString => Some("strings end on the line they start on, use `\"\"\"` for text with line breaks"),
+        ParserErrorKind::TrailingCharacters => Some("put each entry on its own line, comments start with `#`"),
+        ParserErrorKind::InvalidEscape => Some("the escape sequences are `\\\"`, `\\\\`, `\\n`, `\\r`, `\\t` and `\\u{XXXX}`"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use std::borrow::Cow;
+    use std::io;
+    use {Ion, IonError, Parser, Resolver};
+
+    fn error(source: &str) -> IonError {
+        source.parse::<Ion>().unwrap_err()
+    }
+
+    fn only<'s>(source: &'s str) -> impl Fn(Option<&str>) -> Option<Cow<'s, str>> {
+        move |file| if file.is_none() { Some(Cow::Borrowed(source)) } else { None }
+    }
+
+    #[test]
+    fn render_plain() {
+        let source = "[CONTRACT]\nid = 1\nmarkets = [\"abc\"";
+        let expected = concat!(
+            "error[E0004]: Cannot finish an array\n",
+            " --> 3:17\n",
+            "  |\n",
+            "3 | markets = [\"abc\"\n",
+            "  |                 ^\n",
+            "  = hint: close the array with `]`\n",
+        );
+        assert_eq!(expected, Renderer::new().render(only(source), &error(source)));
+    }
+
+    #[test]
+    fn render_underline_and_file() {
+        let source = "[A]\n\tvalue = 99999999999999999999 # too big\r\n";
+        let mut p = Parser::new(source).with_file_name("hotel.ion");
+        p.read();
+
+        let expected = concat!(
+            "error[E0007]: Integer is out of range for a 64-bit signed integer\n",
+            " --> hotel.ion:2:10\n",
+            "  |\n",
+            "2 | \tvalue = 99999999999999999999 # too big\n",
+            "  | \t        ^^^^^^^^^^^^^^^^^^^^\n",
+            "  = hint: use a float for larger numbers, like `1e20`\n",
+        );
+        assert_eq!(expected, Renderer::new().render_error(source, &p.errors[0]));
+    }
+
+    #[test]
+    fn render_multiple_and_other_errors() {
+        let source = "a = 1 2\nb = \"\\q\"";
+        let mut p = Parser::new(source).with_recovery();
+        p.read();
+        let rendered = Renderer::new().render(only(source), &IonError::ParserErrors(p.errors));
+        assert!(rendered.starts_with("error[E0003]: Expected '='\n --> 1:8\n"));
+        assert!(rendered.contains("\n\nerror[E0008]: Invalid escape sequence\n --> 2:6\n"));
+
+        let rendered = Renderer::new().render(only(source), &IonError::MissingSection("HOTEL".to_owned()));
+        assert_eq!("error: MissingSection(\"HOTEL\")\n", rendered);
+    }
+
+    #[test]
+    fn render_ansi() {
+        let source = "key ?";
+        let rendered = Renderer::new().with_color(true).render(only(source), &error(source));
+        let expected = concat!(
+            "\x1b[1;31merror[E0003]\x1b[0m: \x1b[1mExpected '='\x1b[0m\n",
+            " \x1b[1;34m-->\x1b[0m 1:5\n",
+            "  \x1b[1;34m|\x1b[0m\n",
+            "\x1b[1;34m1 |\x1b[0m key ?\n",
+            "  \x1b[1;34m|\x1b[0m     \x1b[1;31m^\x1b[0m\n",
+            "  \x1b[1;34m=\x1b[0m \x1b[1;36mhint\x1b[0m: entries are written as `key = value`\n",
+        );
+        assert_eq!(expected, rendered);
+    }
+
+    #[test]
+    fn render_included_file() {
+        struct Included;
+        impl Resolver for Included {
+            fn resolve(&self, path: &str, _: Option<&str>) -> io::Result<(String, String)> {
+                Ok((path.to_owned(), "[B]\ny = [2".to_owned()))
+            }
+        }
+
+        let main = "[A]\nx = 1\n#include \"b.ion\"\nz = ?";
+        let mut p = Parser::new(main).with_file_name("main.ion").with_recovery();
+        p.read_with_resolver(&Included);
+        let error = IonError::ParserErrors(p.errors);
+
+        let rendered = Renderer::new().render(|file| match file {
+            Some("main.ion") => Some(Cow::Borrowed(main)),
+            Some("b.ion") => Some(Cow::Owned("[B]\ny = [2".to_owned())),
+            _ => None,
+        }, &error);
+        assert!(rendered.starts_with("error[E0004]: Cannot finish an array\n --> b.ion:2:7\n  |\n2 | y = [2\n"), "{}", rendered);
+        assert!(rendered.contains("error[E0001]: Cannot read a value\n --> main.ion:4:5\n  |\n4 | z = ?\n"), "{}", rendered);
+
+        let rendered = Renderer::new().render(|file| if file == Some("main.ion") { Some(Cow::Borrowed(main)) } else { None }, &error);
+        assert!(rendered.starts_with("error[E0004]: Cannot finish an array\n --> b.ion:2:7\n  = hint: close the array with `]`\n"), "{}", rendered);
+        assert!(rendered.contains("4 | z = ?\n"), "{}", rendered);
+    }
+}
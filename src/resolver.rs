@@ -0,0 +1,74 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Loads the files named by `#include "..."` directives
+pub trait Resolver {
+    /// Returns the name and the content of the file `path` included from the file named `from`,
+    /// `from` is `None` for includes in an unnamed input. The returned name identifies the file
+    /// in `ParserError`s, when detecting include cycles and as `from` for its own includes.
+    fn resolve(&self, path: &str, from: Option<&str>) -> io::Result<(String, String)>;
+}
+
+/// Reads included files from the filesystem. Paths are relative to the directory of the
+/// including file, or to the root directory for includes in an unnamed input.
+#[derive(Debug, Clone)]
+pub struct FsResolver {
+    root: PathBuf,
+}
+
+impl FsResolver {
+    /// Resolves the includes of an unnamed input relative to the current directory
+    pub fn new() -> FsResolver {
+        FsResolver::with_root(".")
+    }
+
+    pub fn with_root<P: Into<PathBuf>>(root: P) -> FsResolver {
+        FsResolver { root: root.into() }
+    }
+}
+
+impl Default for FsResolver {
+    fn default() -> FsResolver {
+        FsResolver::new()
+    }
+}
+
+impl Resolver for FsResolver {
+    fn resolve(&self, path: &str, from: Option<&str>) -> io::Result<(String, String)> {
+        let dir = match from.and_then(|from| Path::new(from).parent()) {
+            Some(dir) => dir,
+            None => &self.root,
+        };
+        let path = normalize(&dir.join(path));
+        let content = fs::read_to_string(&path)?;
+        Ok((path.to_string_lossy().into_owned(), content))
+    }
+}
+
+// removes `.` and folds `..` into the preceding component, so that every spelling of a path
+// gets the same name
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir if matches!(out.components().next_back(), Some(Component::Normal(_))) => { out.pop(); },
+            c => out.push(c.as_os_str()),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+    use std::path::Path;
+
+    #[test]
+    fn normalize_path() {
+        assert_eq!(Path::new("a/c.ion"), normalize(Path::new("./a/b/../c.ion")));
+        assert_eq!(Path::new("../a.ion"), normalize(Path::new("../a.ion")));
+        assert_eq!(Path::new("/a.ion"), normalize(Path::new("/x/../a.ion")));
+    }
+}
@@ -0,0 +1,157 @@
+/// The kind of a line seen by `Splitter`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Line {
+    /// Starts with a `[SECTION]` header
+    Section,
+    /// A table row
+    Row,
+    Other,
+}
+
+// Finds the section headers and table rows of ion text fed line by line, without parsing it.
+// It follows just enough of the grammar to tell a `[` starting a section from one in a string,
+// an array or a value on the line after its `=`, all of which may span lines.
+#[derive(Debug, Default, Clone)]
+pub struct Splitter {
+    // the closing quote of the string the previous line ended in
+    quote: Option<&'static str>,
+    // how deeply the previous line ended nested in arrays and dictionaries
+    depth: usize,
+    // the previous line ended with `=`, its value starts on this line
+    value_follows: bool,
+}
+
+impl Splitter {
+    pub fn new() -> Splitter {
+        Splitter::default()
+    }
+
+    pub fn line(&mut self, line: &str) -> Line {
+        let mut rest = line;
+        if let Some(quote) = self.quote {
+            match string_end(rest, quote) {
+                Some(end) => rest = &rest[end..],
+                None => return Line::Other,
+            }
+            self.quote = None;
+        } else if self.depth == 0 && !self.value_follows {
+            match rest.bytes().find(|&b| b != b' ' && b != b'\t') {
+                Some(b'[') => return Line::Section,
+                Some(b'|') => return Line::Row,
+                _ => (),
+            }
+        }
+
+        self.value_follows = false;
+        self.scan(rest);
+        Line::Other
+    }
+
+    // follows the strings and brackets of `rest`, the part of a line outside of strings
+    fn scan(&mut self, rest: &str) {
+        let bytes = rest.as_bytes();
+        let mut i = 0;
+        while let Some(n) = bytes[i..].iter().position(|b| matches!(b, b'"' | b'\'' | b'[' | b'{' | b']' | b'}' | b'#')) {
+            i += n;
+            match bytes[i] {
+                b'"' | b'\'' => {
+                    let quote = match &rest[i..] {
+                        s if s.starts_with("\"\"\"") => "\"\"\"",
+                        s if s.starts_with("\'\'\'") => "\'\'\'",
+                        s if s.starts_with('"') => "\"",
+                        _ => "\'",
+                    };
+                    match string_end(&rest[i + quote.len()..], quote) {
+                        Some(end) => i += quote.len() + end,
+                        None => {
+                            self.quote = Some(quote);
+                            return;
+                        },
+                    }
+                    continue;
+                },
+                b'[' | b'{' => self.depth += 1,
+                b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+                _ => break,
+            }
+            i += 1;
+        }
+        let end = if bytes.get(i) == Some(&b'#') { i } else { rest.len() };
+        self.value_follows = self.depth == 0 && rest[..end].trim_end().ends_with('=');
+    }
+}
+
+/// The name of the section a `Line::Section` line starts, the text up to the `]`
+pub fn section_name(line: &str) -> &str {
+    let line = line.trim_start();
+    let name = line.strip_prefix('[').unwrap_or(line).trim_start_matches([' ', '\t']);
+    &name[..name.find(']').unwrap_or(name.len())]
+}
+
+/// The byte offset and the name of every section header in `input`
+pub fn section_starts(input: &str) -> Vec<(usize, &str)> {
+    let mut splitter = Splitter::new();
+    let mut starts = Vec::new();
+    let mut pos = 0;
+    for line in input.split_inclusive('\n') {
+        if splitter.line(line) == Line::Section {
+            starts.push((pos, section_name(line)));
+        }
+        pos += line.len();
+    }
+    starts
+}
+
+// the end of the closing `quote` in `s`, a backslash escapes the next character in `"` strings.
+// Like the lenient parser, a string without a closing quote on its line continues on the next.
+fn string_end(s: &str, quote: &str) -> Option<usize> {
+    let escapes = quote.starts_with('"');
+    let bytes = s.as_bytes();
+    let first = quote.as_bytes()[0];
+    let mut from = 0;
+    while let Some(i) = bytes[from..].iter().position(|&b| b == first || b == b'\\').map(|i| from + i) {
+        if bytes[i] == b'\\' {
+            let escaped = if escapes { s[i + 1..].chars().next().map_or(0, char::len_utf8) } else { 0 };
+            from = i + 1 + escaped;
+        } else if s[i..].starts_with(quote) {
+            return Some(i + quote.len());
+        } else {
+            from = i + 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{section_starts, Line, Splitter};
+
+    fn kinds(input: &str) -> Vec<Line> {
+        let mut splitter = Splitter::new();
+        input.lines().map(|line| splitter.line(line)).collect()
+    }
+
+    #[test]
+    fn sections_and_rows() {
+        use self::Line::{Other, Row, Section};
+
+        let input = concat!(
+            "[A]\n",
+            "  | a | [b] |\n",
+            "text = \"\"\"\n",
+            "[not a section]\n",
+            "\"\"\"\n",
+            "nested = [\n",
+            "  [1, 2], \"]\" # ]\n",
+            "]\n",
+            "later =\n",
+            "  [3]\n",
+            "quote = \"a \\\" [\"\n",
+            "raw = '''[''' \n",
+            "\t[B.c] # comment\n",
+        );
+        let expected = vec![Section, Row, Other, Other, Other, Other, Other, Other, Other, Other, Other, Other, Section];
+        assert_eq!(expected, kinds(input));
+        assert_eq!(vec![(0, "A"), (input.rfind('\t').unwrap(), "B.c")], section_starts(input));
+    }
+}
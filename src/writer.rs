@@ -1,16 +1,25 @@
 use std::{ convert  };
 use std::io::{ self, Write };
+use ion::{Cell, InferredCell, Key, Quoted};
 use Value;
 
 pub type Result = io::Result<()>;
 
 pub struct Writer<W: Write> {
-    writer: Box<W>
+    writer: Box<W>,
+    infer_cells: bool,
 }
 
 impl<W: Write> Writer<W> {
     pub fn new(w: Box<W>) -> Writer<W> {
-        Writer { writer: w }
+        Writer { writer: w, infer_cells: false }
+    }
+
+    /// Quotes the string cells of rows that `Parser::with_cell_type_inference` would read as
+    /// another value, like `1`, `true` or an empty string, so that they read back as strings
+    pub fn with_cell_type_inference(mut self) -> Self {
+        self.infer_cells = true;
+        self
     }
 
     pub fn write(&mut self, text: &str) -> Result {
@@ -25,17 +34,28 @@ impl<W: Write> Writer<W> {
     }
 
     pub fn key_value<'a, I: Into<String>>(&mut self, name: &str, value: I) -> Result {
-        self.write(name)?;
+        self.write(&Key(name).to_string())?;
         self.write(" = ")?;
         self.write(&value.into())?;
         self.write("\n")
     }
+
+    pub fn row(&mut self, cells: &[Value]) -> Result {
+        for cell in cells {
+            if self.infer_cells {
+                self.write(&format!("| {} ", InferredCell(cell)))?;
+            } else {
+                self.write(&format!("| {} ", Cell(cell)))?;
+            }
+        }
+        self.write("|\n")
+    }
 }
 
 impl<'a> convert::From<&'a Value> for String {
     fn from(v: &Value) ->  String {
         match v {
-            &Value::String(ref s) => format!("\"{}\"", s),
+            &Value::String(ref s) => Quoted(s).to_string(),
             &Value::Array(ref ary) => {
                 let mut out = String::new();
                 let mut first = true;
@@ -55,7 +75,7 @@ impl<'a> convert::From<&'a Value> for String {
 
 #[cfg(test)]
 mod tests {
-    use { Value, Writer };
+    use { Element, Parser, Value, Writer };
 
     #[test]
     #[allow(unused_must_use)]
@@ -66,21 +86,54 @@ mod tests {
             let mut w = Writer::new(Box::new(unsafe { s.as_mut_vec() }));
             w.section("TEST");
             w.key_value("string", &Value::String("bar".to_string()));
+            w.key_value("escaped", &Value::String("say \"hi\"".to_string()));
+            w.key_value("multi_line", &Value::String("first\nsecond".to_string()));
+            w.key_value("check-in time", &Value::Integer(14));
             w.key_value("integer", &Value::Integer(1));
             w.key_value("boolean", &Value::Boolean(true));
             w.key_value("array", &Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::String("foobar".to_string())]));
             let mut dict = BTreeMap::new();
             dict.insert("foo".to_string(), Value::String("bar".to_string()));
             w.key_value("dict", &Value::Dictionary(dict));
+            w.row(&[Value::String("a|b".to_string()), Value::String(" padded ".to_string()), Value::Integer(3)]);
         }
 
         assert_eq!(r#"[TEST]
 string = "bar"
+escaped = "say \"hi\""
+multi_line = """
+first
+second
+"""
+"check-in time" = 14
 integer = 1
 boolean = true
 array = [ 1, 2, "foobar" ]
 dict = { foo = "bar" }
+| a\|b | " padded " | 3 |
 "#, s);
 
     }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn writer_with_cell_type_inference() {
+        let row = [Value::new_string("1"), Value::new_string("true"), Value::new_string(""), Value::new_string("x"), Value::Integer(1)];
+        let mut s = String::new();
+        {
+            let mut w = Writer::new(Box::new(unsafe { s.as_mut_vec() }));
+            w.row(&row);
+        }
+        assert_eq!("| 1 | true |  | x | 1 |\n", s);
+
+        let mut s = String::new();
+        {
+            let mut w = Writer::new(Box::new(unsafe { s.as_mut_vec() })).with_cell_type_inference();
+            w.row(&row);
+        }
+        assert_eq!("| \"1\" | \"true\" | \"\" | x | 1 |\n", s);
+
+        let mut p = Parser::new(&s).with_cell_type_inference();
+        assert_eq!(Some(Element::Row(row.to_vec())), p.next());
+    }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 #[macro_use] extern crate ion;
+use ion::ParserErrorKind;
 use std::fs::read_to_string;
 
 fn read_file<T: AsRef<str>>(filename: T) -> String {
@@ -13,6 +14,13 @@ fn read_err_ion<T: AsRef<str>>(filename: T) -> ion::IonError {
     read_file(filename).parse::<ion::Ion>().unwrap_err()
 }
 
+fn parser_errors(err: ion::IonError) -> Vec<ion::ParserError> {
+    match err {
+        ion::IonError::ParserErrors(errors) => errors,
+        err => panic!("Expected parser errors, got {:?}", err),
+    }
+}
+
 #[test]
 fn test_ion() {
     let ion = read_ion("tests/data/test.ion");
@@ -31,16 +39,76 @@ fn hotel_ion() {
 
 #[test]
 fn broken_array_and_eof() {
-    let ion_err = read_err_ion("tests/data/broken_array_and_eof.ion");
-    let expected = "ParserErrors([ParserError { lo: 55, hi: 55, desc: \"Cannot finish an array\" }])";
+    let errors = parser_errors(read_err_ion("tests/data/broken_array_and_eof.ion"));
 
-    assert_eq!(expected, ion_err.to_string());
+    assert_eq!(1, errors.len());
+    assert_eq!(ParserErrorKind::UnterminatedArray, errors[0].kind);
+    assert_eq!((55, 55), (errors[0].lo, errors[0].hi));
+    assert_eq!((3, 17), (errors[0].line, errors[0].col));
+    assert_eq!("3:17: Cannot finish an array [E0004]", errors[0].to_string());
 }
 
 #[test]
 fn broken_dictionary_and_eof() {
-    let ion_err = read_err_ion("tests/data/broken_dictionary_and_eof.ion");
-    let expected = "ParserErrors([ParserError { lo: 67, hi: 67, desc: \"Cannot finish a dictionary\" }])";
+    let errors = parser_errors(read_err_ion("tests/data/broken_dictionary_and_eof.ion"));
+
+    assert_eq!(1, errors.len());
+    assert_eq!(ParserErrorKind::UnterminatedDictionary, errors[0].kind);
+    assert_eq!((67, 67), (errors[0].lo, errors[0].hi));
+    assert_eq!("3:24: Cannot finish a dictionary [E0005]", errors[0].to_string());
+}
+
+#[test]
+fn include_file() {
+    let ion = ion::Ion::from_file("tests/data/include/main.ion").unwrap();
+
+    assert_eq!(Some(&ion::Value::Integer(1)), ion.get("CONTRACT").unwrap().get("id"));
+    assert_eq!(2, ion.get("CURRENCIES").unwrap().rows_without_header().len());
+    assert!(ion.get("BRAND").is_some());
+}
+
+#[test]
+fn include_file_error() {
+    let errors = parser_errors(ion::Ion::from_file("tests/data/include/broken.ion").unwrap_err());
+
+    assert_eq!(1, errors.len());
+    assert_eq!(ParserErrorKind::InvalidValue, errors[0].kind);
+    assert_eq!("tests/data/include/shared/broken.ion:3:1: Cannot read a value [E0001]", errors[0].to_string());
+
+    match ion::Ion::from_file("tests/data/include/missing.ion") {
+        Err(ion::IonError::Io(path, kind, message)) => {
+            assert_eq!("tests/data/include/missing.ion", path);
+            assert_eq!(std::io::ErrorKind::NotFound, kind);
+            assert!(!message.is_empty());
+        },
+        other => panic!("Expected an I/O error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn broken_array_and_eof_recovering() {
+    let (ion, errors) = ion::Ion::from_str_recovering(&read_file("tests/data/broken_array_and_eof.ion"));
+
+    assert_eq!(1, errors.len());
+    assert_eq!("Cannot finish an array", errors[0].desc);
+    assert!(ion.get("CONTRACT").is_some());
+}
+
+#[test]
+fn index_and_reader_match_ion() {
+    for file in &["tests/data/test.ion", "tests/data/hotel.ion"] {
+        let input = read_file(file);
+        let ion = read_ion(file);
+
+        let mut index = ion::IonIndex::new(&input);
+        assert_eq!(ion.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), index.names().collect::<Vec<_>>());
+        for (name, section) in ion.iter() {
+            assert_eq!(Some(section), index.get(name).unwrap(), "{} in {}", name, file);
+        }
 
-    assert_eq!(expected, ion_err.to_string());
+        for result in ion::SectionReader::new(input.as_bytes()) {
+            let (name, section) = result.unwrap();
+            assert_eq!(ion.get(&name), Some(&section), "{} in {}", name, file);
+        }
+    }
 }